@@ -0,0 +1,22 @@
+// Feeds arbitrary bytes to the reader, which has to report bad input as an error rather than
+// panic. Everything read is written back out to exercise the printer too. Run with
+// cargo fuzz run reader.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use lisp::{write, Reader};
+
+fuzz_target!(|data: &[u8]| {
+    let mut reader = Reader::new(data);
+
+    //Every call either reads a datum or skips past bad input, so this always finishes
+    for _ in 0..=data.len() {
+        match reader.read() {
+            Ok(Some((expr, _))) => {
+                write(&expr);
+            }
+            Ok(None) => break,
+            Err(_) => {}
+        }
+    }
+});
@@ -0,0 +1,186 @@
+// Derive and attribute macros for exposing Rust structs and functions to the interpreter.
+// These are re-exported by the lisp crate, and the generated code refers to it as ::lisp.
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, AttributeArgs, Data, DeriveInput, Fields, FnArg, ItemFn, Lit, Meta,
+    NestedMeta,
+};
+
+//MyPoint becomes my-point. A run of capitals is one word, so HTTPServer becomes http-server.
+fn kebab_from_camel(name: &str) -> String {
+    let chars = name.chars().collect::<Vec<_>>();
+    let mut kebab = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() {
+            //A word starts after a lowercase letter or digit, or at the last capital of a run
+            //when a lowercase letter follows it
+            let after_word = i > 0 && !chars[i - 1].is_uppercase();
+            let ends_run = i > 0 && chars.get(i + 1).is_some_and(|next| next.is_lowercase());
+            if after_word || ends_run {
+                kebab.push('-');
+            }
+            kebab.extend(c.to_lowercase());
+        } else {
+            kebab.push(c);
+        }
+    }
+    kebab
+}
+
+//max_x becomes max-x
+fn kebab_from_snake(name: &str) -> String {
+    name.trim_start_matches("r#").replace('_', "-")
+}
+
+// Implements LispRecord, FromLisp and IntoLisp for a struct with named fields, so it can be
+// passed to and returned from registered functions as a record. The record type is named
+// after the struct in kebab case; Interpreter::register_record binds its procedures.
+#[proc_macro_derive(LispRecord)]
+pub fn derive_lisp_record(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let ident = &input.ident;
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => fields.named.iter().collect::<Vec<_>>(),
+            _ => {
+                return syn::Error::new_spanned(
+                    ident,
+                    "LispRecord needs a struct with named fields",
+                )
+                .to_compile_error()
+                .into();
+            }
+        },
+        _ => {
+            return syn::Error::new_spanned(ident, "LispRecord can only be derived for structs")
+                .to_compile_error()
+                .into();
+        }
+    };
+
+    if !input.generics.params.is_empty() {
+        return syn::Error::new_spanned(&input.generics, "LispRecord can't be generic")
+            .to_compile_error()
+            .into();
+    }
+
+    let type_name = kebab_from_camel(&ident.to_string());
+    let field_idents = fields
+        .iter()
+        .map(|field| field.ident.clone().unwrap())
+        .collect::<Vec<_>>();
+    let field_names = field_idents
+        .iter()
+        .map(|id| kebab_from_snake(&id.to_string()))
+        .collect::<Vec<_>>();
+    let field_types = fields.iter().map(|field| &field.ty);
+    let indices = 0..fields.len();
+
+    let expanded = quote! {
+        impl ::lisp::LispRecord for #ident {
+            fn record_type() -> ::std::rc::Rc<::lisp::RecordType> {
+                thread_local! {
+                    static RECORD_TYPE: ::std::rc::Rc<::lisp::RecordType> =
+                        ::std::rc::Rc::new(::lisp::RecordType::new(
+                            ::std::string::String::from(#type_name),
+                            vec![#(::std::string::String::from(#field_names)),*],
+                        ));
+                }
+                RECORD_TYPE.with(::std::rc::Rc::clone)
+            }
+        }
+
+        impl ::lisp::IntoLisp for #ident {
+            fn into_lisp(
+                self,
+            ) -> ::std::result::Result<::std::rc::Rc<::lisp::Expression>, ::std::string::String> {
+                let record = ::lisp::Record::new(
+                    <#ident as ::lisp::LispRecord>::record_type(),
+                    vec![#(::lisp::IntoLisp::into_lisp(self.#field_idents)?),*],
+                );
+                ::std::result::Result::Ok(::std::rc::Rc::new(::lisp::Expression::Record(record)))
+            }
+        }
+
+        impl ::lisp::FromLisp for #ident {
+            fn from_lisp(
+                expr: &::std::rc::Rc<::lisp::Expression>,
+            ) -> ::std::result::Result<Self, ::std::string::String> {
+                match expr.as_ref() {
+                    ::lisp::Expression::Record(record)
+                        if record.is_instance(&<#ident as ::lisp::LispRecord>::record_type()) =>
+                    {
+                        ::std::result::Result::Ok(#ident {
+                            #(#field_idents: <#field_types as ::lisp::FromLisp>::from_lisp(
+                                &record.get(#indices),
+                            )?),*
+                        })
+                    }
+                    _ => ::std::result::Result::Err(::lisp::type_error(#type_name, expr)),
+                }
+            }
+        }
+    };
+
+    expanded.into()
+}
+
+// Generates register_<fn>(&mut Interpreter) alongside a function, which binds it under its
+// name in kebab case, or the one given by #[lisp_fn(name = "...")]. The arguments and
+// result are converted as in Interpreter::register_typed.
+#[proc_macro_attribute]
+pub fn lisp_fn(args: TokenStream, input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args as AttributeArgs);
+    let func = parse_macro_input!(input as ItemFn);
+    let ident = &func.sig.ident;
+
+    let mut name = kebab_from_snake(&ident.to_string());
+    for arg in args.iter() {
+        match arg {
+            NestedMeta::Meta(Meta::NameValue(pair)) if pair.path.is_ident("name") => {
+                match &pair.lit {
+                    Lit::Str(lit) => name = lit.value(),
+                    lit => {
+                        return syn::Error::new_spanned(lit, "Expected a string for name")
+                            .to_compile_error()
+                            .into();
+                    }
+                }
+            }
+            arg => {
+                return syn::Error::new_spanned(arg, "Expected name = \"...\"")
+                    .to_compile_error()
+                    .into();
+            }
+        }
+    }
+
+    if let Some(receiver) = func
+        .sig
+        .inputs
+        .iter()
+        .find(|arg| matches!(arg, FnArg::Receiver(_)))
+    {
+        return syn::Error::new_spanned(receiver, "lisp_fn can't take self")
+            .to_compile_error()
+            .into();
+    }
+
+    let vis = &func.vis;
+    let register = format_ident!("register_{}", ident, span = Span::call_site());
+
+    let expanded = quote! {
+        #func
+
+        #vis fn #register(interpreter: &mut ::lisp::Interpreter) {
+            interpreter.register_typed(#name, #ident);
+        }
+    };
+
+    expanded.into()
+}
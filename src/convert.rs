@@ -0,0 +1,321 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::hash::Hash;
+use std::rc::Rc;
+
+use crate::types::*;
+
+// Converts a Lisp value into a Rust value, failing with a message naming the expected and
+// actual types
+pub trait FromLisp: Sized {
+    fn from_lisp(expr: &Rc<Expression>) -> Result<Self, String>;
+}
+
+// Converts a Rust value into a Lisp value, failing for values with no Lisp equivalent, like
+// integers too wide for a Lisp integer
+pub trait IntoLisp {
+    fn into_lisp(self) -> Result<Rc<Expression>, String>;
+}
+
+pub fn type_error(expected: &str, expr: &Expression) -> String {
+    format!("Expected {}, got {}", expected, expr.type_name())
+}
+
+impl FromLisp for Rc<Expression> {
+    fn from_lisp(expr: &Rc<Expression>) -> Result<Self, String> {
+        Ok(Rc::clone(expr))
+    }
+}
+
+impl IntoLisp for Rc<Expression> {
+    fn into_lisp(self) -> Result<Rc<Expression>, String> {
+        Ok(self)
+    }
+}
+
+impl IntoLisp for Expression {
+    fn into_lisp(self) -> Result<Rc<Expression>, String> {
+        Ok(Rc::new(self))
+    }
+}
+
+impl IntoLisp for () {
+    fn into_lisp(self) -> Result<Rc<Expression>, String> {
+        Ok(Rc::new(Expression::Unspecified))
+    }
+}
+
+//Integers are stored as i32, so wider types are range checked in both directions
+macro_rules! impl_integer {
+    ($($t:ty),*) => {
+        $(
+            impl FromLisp for $t {
+                fn from_lisp(expr: &Rc<Expression>) -> Result<Self, String> {
+                    match expr.as_ref() {
+                        Expression::Numeric(Number::Integer(x)) => <$t>::try_from(*x)
+                            .map_err(|_| format!("Integer {} is out of range for {}", x, stringify!($t))),
+                        _ => Err(type_error("integer", expr)),
+                    }
+                }
+            }
+
+            impl IntoLisp for $t {
+                fn into_lisp(self) -> Result<Rc<Expression>, String> {
+                    i32::try_from(self)
+                        .map(|x| Rc::new(Expression::from(x)))
+                        .map_err(|_| format!("Integer {} is out of range for a Lisp integer", self))
+                }
+            }
+        )*
+    };
+}
+
+impl_integer!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+//Integers are accepted where a real is expected
+impl FromLisp for f32 {
+    fn from_lisp(expr: &Rc<Expression>) -> Result<Self, String> {
+        match expr.as_ref() {
+            Expression::Numeric(Number::Float(x)) => Ok(*x),
+            Expression::Numeric(Number::Integer(x)) => Ok(*x as f32),
+            _ => Err(type_error("real", expr)),
+        }
+    }
+}
+
+//Reals are single precision, so they widen to the double they print as, and doubles that
+//would lose precision are an error
+impl FromLisp for f64 {
+    fn from_lisp(expr: &Rc<Expression>) -> Result<Self, String> {
+        f32::from_lisp(expr).map(Number::float_to_f64)
+    }
+}
+
+impl IntoLisp for f32 {
+    fn into_lisp(self) -> Result<Rc<Expression>, String> {
+        Ok(Rc::new(Expression::from(self)))
+    }
+}
+
+impl IntoLisp for f64 {
+    fn into_lisp(self) -> Result<Rc<Expression>, String> {
+        match Number::float_from_f64(self) {
+            Some(x) => Ok(Rc::new(Expression::from(x))),
+            None => Err(format!("Real {} needs more than single precision", self)),
+        }
+    }
+}
+
+impl FromLisp for bool {
+    fn from_lisp(expr: &Rc<Expression>) -> Result<Self, String> {
+        match expr.as_ref() {
+            Expression::Boolean(b) => Ok(*b),
+            _ => Err(type_error("boolean", expr)),
+        }
+    }
+}
+
+impl IntoLisp for bool {
+    fn into_lisp(self) -> Result<Rc<Expression>, String> {
+        Ok(Rc::new(Expression::Boolean(self)))
+    }
+}
+
+impl FromLisp for char {
+    fn from_lisp(expr: &Rc<Expression>) -> Result<Self, String> {
+        match expr.as_ref() {
+            Expression::Char(c) => Ok(*c),
+            _ => Err(type_error("char", expr)),
+        }
+    }
+}
+
+impl IntoLisp for char {
+    fn into_lisp(self) -> Result<Rc<Expression>, String> {
+        Ok(Rc::new(Expression::Char(self)))
+    }
+}
+
+impl FromLisp for String {
+    fn from_lisp(expr: &Rc<Expression>) -> Result<Self, String> {
+        match expr.as_ref() {
+            Expression::String(s) => Ok(s.clone()),
+            _ => Err(type_error("string", expr)),
+        }
+    }
+}
+
+impl IntoLisp for String {
+    fn into_lisp(self) -> Result<Rc<Expression>, String> {
+        Ok(Rc::new(Expression::String(self)))
+    }
+}
+
+impl IntoLisp for &str {
+    fn into_lisp(self) -> Result<Rc<Expression>, String> {
+        Ok(Rc::new(Expression::from(self)))
+    }
+}
+
+//Both lists and vectors convert to a Vec, which converts back to a list
+impl<T: FromLisp> FromLisp for Vec<T> {
+    fn from_lisp(expr: &Rc<Expression>) -> Result<Self, String> {
+        match expr.as_ref() {
+            Expression::SExpr(list) => list.iter().map(T::from_lisp).collect(),
+            Expression::Vector(v) => v.borrow().iter().map(T::from_lisp).collect(),
+            _ => Err(type_error("list", expr)),
+        }
+    }
+}
+
+impl<T: IntoLisp> IntoLisp for Vec<T> {
+    fn into_lisp(self) -> Result<Rc<Expression>, String> {
+        let list = self
+            .into_iter()
+            .map(IntoLisp::into_lisp)
+            .collect::<Result<_, _>>()?;
+        Ok(Rc::new(Expression::SExpr(list)))
+    }
+}
+
+//#f stands for a missing value, so a present value can't convert to #f
+impl<T: FromLisp> FromLisp for Option<T> {
+    fn from_lisp(expr: &Rc<Expression>) -> Result<Self, String> {
+        match expr.as_ref() {
+            Expression::Boolean(false) => Ok(None),
+            _ => T::from_lisp(expr).map(Some),
+        }
+    }
+}
+
+impl<T: IntoLisp> IntoLisp for Option<T> {
+    fn into_lisp(self) -> Result<Rc<Expression>, String> {
+        match self {
+            Some(x) => match x.into_lisp()? {
+                expr if matches!(expr.as_ref(), Expression::Boolean(false)) => Err(String::from(
+                    "Cannot convert a present value of #f, since #f stands for a missing value",
+                )),
+                expr => Ok(expr),
+            },
+            None => Ok(Rc::new(Expression::Boolean(false))),
+        }
+    }
+}
+
+impl<K: FromLisp + Eq + Hash, V: FromLisp> FromLisp for HashMap<K, V> {
+    fn from_lisp(expr: &Rc<Expression>) -> Result<Self, String> {
+        match expr.as_ref() {
+            Expression::HashTable(table) => table
+                .borrow()
+                .entries()
+                .iter()
+                .map(|(k, v)| Ok((K::from_lisp(k)?, V::from_lisp(v)?)))
+                .collect(),
+            _ => Err(type_error("hash-table", expr)),
+        }
+    }
+}
+
+//Maps become equal? hash tables
+impl<K: IntoLisp, V: IntoLisp> IntoLisp for HashMap<K, V> {
+    fn into_lisp(self) -> Result<Rc<Expression>, String> {
+        let mut table = HashTable::new(Equivalence::Equal);
+        for (k, v) in self.into_iter() {
+            table.insert(&k.into_lisp()?, v.into_lisp()?);
+        }
+        Ok(Rc::new(Expression::HashTable(RefCell::new(table))))
+    }
+}
+
+//Tuples are lists of exactly their length
+macro_rules! impl_tuple {
+    ($len:expr; $($t:ident $idx:tt),*) => {
+        impl<$($t: FromLisp),*> FromLisp for ($($t,)*) {
+            fn from_lisp(expr: &Rc<Expression>) -> Result<Self, String> {
+                match expr.as_ref() {
+                    Expression::SExpr(list) if list.len() == $len => {
+                        Ok(($($t::from_lisp(&list[$idx])?,)*))
+                    }
+                    _ => Err(type_error(concat!("list of length ", $len), expr)),
+                }
+            }
+        }
+
+        impl<$($t: IntoLisp),*> IntoLisp for ($($t,)*) {
+            fn into_lisp(self) -> Result<Rc<Expression>, String> {
+                Ok(Rc::new(Expression::SExpr(vec![$(self.$idx.into_lisp()?),*])))
+            }
+        }
+    };
+}
+
+impl_tuple!(1; A 0);
+impl_tuple!(2; A 0, B 1);
+impl_tuple!(3; A 0, B 1, C 2);
+impl_tuple!(4; A 0, B 1, C 2, D 3);
+impl_tuple!(5; A 0, B 1, C 2, D 3, E 4);
+impl_tuple!(6; A 0, B 1, C 2, D 3, E 4, F 5);
+
+// The result of a typed Rust function, which is either a value or a Result carrying an
+// error message
+pub trait IntoLispResult {
+    fn into_lisp_result(self) -> Result<Rc<Expression>, String>;
+}
+
+impl<T: IntoLisp> IntoLispResult for T {
+    fn into_lisp_result(self) -> Result<Rc<Expression>, String> {
+        self.into_lisp()
+    }
+}
+
+impl<T: IntoLisp> IntoLispResult for Result<T, String> {
+    fn into_lisp_result(self) -> Result<Rc<Expression>, String> {
+        self.and_then(IntoLisp::into_lisp)
+    }
+}
+
+// A Rust function whose arguments can each be converted from Lisp values. Args is the
+// tuple of argument types, which keeps the impls for each arity apart.
+pub trait LispFn<Args> {
+    fn arity(&self) -> Arity;
+    fn call_lisp(&self, name: &str, args: &[Rc<Expression>]) -> Result<Rc<Expression>, String>;
+}
+
+macro_rules! impl_lisp_fn {
+    ($len:expr; $($t:ident $idx:tt),*) => {
+        impl<Func, Ret, $($t),*> LispFn<($($t,)*)> for Func
+        where
+            Func: Fn($($t),*) -> Ret,
+            Ret: IntoLispResult,
+            $($t: FromLisp),*
+        {
+            fn arity(&self) -> Arity {
+                Arity::Exactly($len)
+            }
+
+            #[allow(unused_variables)]
+            fn call_lisp(&self, name: &str, args: &[Rc<Expression>]) -> Result<Rc<Expression>, String> {
+                self($($t::from_lisp(&args[$idx]).map_err(|msg| {
+                    format!("{} in argument {} to {}", msg, $idx + 1, name)
+                })?),*)
+                .into_lisp_result()
+            }
+        }
+    };
+}
+
+impl_lisp_fn!(0;);
+impl_lisp_fn!(1; A 0);
+impl_lisp_fn!(2; A 0, B 1);
+impl_lisp_fn!(3; A 0, B 1, C 2);
+impl_lisp_fn!(4; A 0, B 1, C 2, D 3);
+impl_lisp_fn!(5; A 0, B 1, C 2, D 3, E 4);
+impl_lisp_fn!(6; A 0, B 1, C 2, D 3, E 4, F 5);
+
+// A Rust struct exposed to Lisp as a record. The record type must be the same Rc every
+// time, since records are matched to their type by identity. Usually derived with
+// #[derive(LispRecord)].
+pub trait LispRecord: FromLisp + IntoLisp {
+    fn record_type() -> Rc<RecordType>;
+}
@@ -1,43 +1,28 @@
 use crate::types::*;
 use std::rc::Rc;
 
-use crate::eval::eval;
-
-pub fn add(args: &[Rc<Expression>], env: &mut Environment) -> Result<Rc<Expression>, String> {
-    fn add_exprs(acc: Number, x: &Number) -> Number {
-        acc + *x
-    }
-    arithmetic_op(args, env, "add", add_exprs)
+pub fn add(args: &[Rc<Expression>], _env: &mut Environment) -> Result<Rc<Expression>, String> {
+    arithmetic_op(args, "+", Number::checked_add)
 }
 
-pub fn sub(args: &[Rc<Expression>], env: &mut Environment) -> Result<Rc<Expression>, String> {
-    fn add_exprs(acc: Number, x: &Number) -> Number {
-        acc - *x
-    }
-    arithmetic_op(args, env, "sub", add_exprs)
+pub fn sub(args: &[Rc<Expression>], _env: &mut Environment) -> Result<Rc<Expression>, String> {
+    arithmetic_op(args, "-", Number::checked_sub)
 }
 
-pub fn mul(args: &[Rc<Expression>], env: &mut Environment) -> Result<Rc<Expression>, String> {
-    fn add_exprs(acc: Number, x: &Number) -> Number {
-        acc * *x
-    }
-    arithmetic_op(args, env, "sub", add_exprs)
+pub fn mul(args: &[Rc<Expression>], _env: &mut Environment) -> Result<Rc<Expression>, String> {
+    arithmetic_op(args, "*", Number::checked_mul)
 }
 
-pub fn div(args: &[Rc<Expression>], env: &mut Environment) -> Result<Rc<Expression>, String> {
-    fn add_exprs(acc: Number, x: &Number) -> Number {
-        acc / *x
-    }
-    arithmetic_op(args, env, "sub", add_exprs)
+pub fn div(args: &[Rc<Expression>], _env: &mut Environment) -> Result<Rc<Expression>, String> {
+    arithmetic_op(args, "/", Number::checked_div)
 }
 
-pub fn less_than(args: &[Rc<Expression>], env: &mut Environment) -> Result<Rc<Expression>, String> {
-    if args.len() != 2 {
-        return Err(format!("Expected 2 arguments to <, found {}", args.len()));
-    }
-
-    let arg1 = eval(Rc::clone(&args[0]), env)?;
-    let arg2 = eval(Rc::clone(&args[1]), env)?;
+pub fn less_than(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let arg1 = &args[0];
+    let arg2 = &args[1];
 
     match (arg1.as_ref(), arg2.as_ref()) {
         (Expression::Numeric(n1), Expression::Numeric(n2)) => {
@@ -54,13 +39,9 @@ pub fn less_than(args: &[Rc<Expression>], env: &mut Environment) -> Result<Rc<Ex
     }
 }
 
-pub fn equal_to(args: &[Rc<Expression>], env: &mut Environment) -> Result<Rc<Expression>, String> {
-    if args.len() != 2 {
-        return Err(format!("Expected 2 arguments to =, found {}", args.len()));
-    }
-
-    let arg1 = eval(Rc::clone(&args[0]), env)?;
-    let arg2 = eval(Rc::clone(&args[1]), env)?;
+pub fn equal_to(args: &[Rc<Expression>], _env: &mut Environment) -> Result<Rc<Expression>, String> {
+    let arg1 = &args[0];
+    let arg2 = &args[1];
 
     match (arg1.as_ref(), arg2.as_ref()) {
         (Expression::Numeric(n1), Expression::Numeric(n2)) => {
@@ -77,81 +58,116 @@ pub fn equal_to(args: &[Rc<Expression>], env: &mut Environment) -> Result<Rc<Exp
     }
 }
 
+//Integer results that don't fit, and integer division by zero, are errors
+fn checked(
+    name: &str,
+    op: fn(Number, Number) -> Option<Number>,
+    a: Number,
+    b: Number,
+) -> Result<Number, String> {
+    op(a, b).ok_or_else(|| match (a, b) {
+        (Number::Integer(_), Number::Integer(0)) => format!("Division by zero in {}", name),
+        _ => format!("Integer overflow in {}", name),
+    })
+}
+
 fn arithmetic_op(
     args: &[Rc<Expression>],
-    env: &mut Environment,
     name: &str,
-    op: fn(Number, &Number) -> Number,
+    op: fn(Number, Number) -> Option<Number>,
 ) -> Result<Rc<Expression>, String> {
-    if args.len() < 2 {
-        return Err(format!("Not enough arguments to {}", name));
-    }
+    //"Cast" every argument to a Number, then fold from the first one. Each procedure takes
+    //at least two arguments, so there is always a first one.
+    let nums = args
+        .iter()
+        .map(|expr| match expr.as_ref() {
+            Expression::Numeric(num) => Ok(*num),
+            _ => Err(format!(
+                "Expected numeric arguments to {}, got {}",
+                name,
+                expr.as_ref()
+            )),
+        })
+        .collect::<Result<Vec<Number>, String>>()?;
+
+    let ans = nums[1..]
+        .iter()
+        .try_fold(nums[0], |acc, x| checked(name, op, acc, *x))?;
+    Ok(Rc::new(Expression::Numeric(ans)))
+}
 
-    //Evaluate all the arguments
-    let mut args_eval = Vec::with_capacity(args.len());
-    for arg in args {
-        args_eval.push(eval(Rc::clone(arg), env)?);
-    }
+pub fn remainder(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let arg1 = &args[0];
+    let arg2 = &args[1];
 
-    //Check for non-numeric arguments
-    if let Some(expr) = args_eval.iter().find(|expr| !expr.is_number()) {
-        return Err(format!("Cannot add non-numeric object {}", expr.as_ref()));
+    match (arg1.as_ref(), arg2.as_ref()) {
+        (Expression::Numeric(a), Expression::Numeric(b)) => {
+            checked("remainder", Number::checked_rem, *a, *b)
+                .map(|ans| Rc::new(Expression::Numeric(ans)))
+        }
+        (Expression::Numeric(_), _) => Err(format!(
+            "Expected numeric arguments to remainder, got {}",
+            arg2.as_ref()
+        )),
+        _ => Err(format!(
+            "Expected numeric arguments to remainder, got {}",
+            arg1.as_ref()
+        )),
     }
+}
 
-    //Start with first argument, "cast" everything to Number, then sum
-    if let Expression::Numeric(first) = args_eval[0].as_ref() {
-        let ans = args_eval[1..]
-            .iter()
-            .map(|expr| {
-                if let Expression::Numeric(num) = expr.as_ref() {
-                    num
-                } else {
-                    unreachable!()
-                }
-            })
-            .fold(*first, op);
-
-        return Ok(Rc::new(Expression::Numeric(ans)));
+//Reads the optional radix argument shared by number->string and string->number
+fn radix_arg(args: &[Rc<Expression>], name: &str) -> Result<u32, String> {
+    match args.get(1).map(|arg| arg.as_ref()) {
+        None => Ok(10),
+        Some(Expression::Numeric(Number::Integer(r))) if [2, 8, 10, 16].contains(r) => {
+            Ok(*r as u32)
+        }
+        Some(expr) => Err(format!(
+            "Expected radix of 2, 8, 10 or 16 in {}, got {}",
+            name, expr
+        )),
     }
-
-    //Just in case
-    let _ = dbg!(args_eval);
-    unreachable!()
 }
 
-pub fn remainder(args: &[Rc<Expression>], env: &mut Environment) -> Result<Rc<Expression>, String> {
-    if args.len() != 2 {
-        return Err(format!(
-            "Expected 2 arguments to remainder, {} were given",
-            args.len(),
-        ));
+pub fn number_to_string(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let radix = radix_arg(args, "number->string")?;
+
+    match args[0].as_ref() {
+        Expression::Numeric(num) => match num.to_string_radix(radix) {
+            Some(s) => Ok(Rc::new(Expression::String(s))),
+            None => Err(format!(
+                "Cannot write inexact number {} in radix {}",
+                num, radix
+            )),
+        },
+        expr => Err(format!(
+            "Expected numeric argument to number->string, got {}",
+            expr
+        )),
     }
+}
 
-    let arg1 = eval(Rc::clone(&args[0]), env)?;
-    let arg2 = eval(Rc::clone(&args[1]), env)?;
-
-    if let Expression::Numeric(a) = arg1.as_ref() {
-        if let Expression::Numeric(b) = arg2.as_ref() {
-            Ok(Rc::new(Expression::Numeric(match a {
-                Number::Integer(x) => match b {
-                    Number::Integer(y) => Number::Integer(*x % *y),
-                    Number::Float(y) => Number::Float(*x as f32 % *y),
-                },
-                Number::Float(x) => match b {
-                    Number::Integer(y) => Number::Float(*x % *y as f32),
-                    Number::Float(y) => Number::Float(*x % *y),
-                },
-            })))
-        } else {
-            Err(format!(
-                "Expected numeric arguments to remainder, got {}",
-                arg1.as_ref()
-            ))
-        }
-    } else {
-        Err(format!(
-            "Expected numeric arguments to remainder, got {}",
-            arg2.as_ref()
-        ))
+pub fn string_to_number(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let radix = radix_arg(args, "string->number")?;
+
+    match args[0].as_ref() {
+        Expression::String(s) => Ok(Rc::new(match Number::parse(s, radix) {
+            Ok(num) => Expression::Numeric(num),
+            Err(_) => Expression::Boolean(false),
+        })),
+        expr => Err(format!(
+            "Expected string argument to string->number, got {}",
+            expr
+        )),
     }
 }
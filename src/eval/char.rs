@@ -0,0 +1,39 @@
+use crate::types::*;
+use std::rc::Rc;
+
+pub fn is_char(args: &[Rc<Expression>], _env: &mut Environment) -> Result<Rc<Expression>, String> {
+    Ok(Rc::new(Expression::from(matches!(
+        args[0].as_ref(),
+        Expression::Char(_)
+    ))))
+}
+
+pub fn char_to_integer(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    match args[0].as_ref() {
+        Expression::Char(c) => Ok(Rc::new(Expression::from(*c as i32))),
+        expr => Err(format!(
+            "Expected char argument to char->integer, got {}",
+            expr
+        )),
+    }
+}
+
+pub fn integer_to_char(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let c = match args[0].as_ref() {
+        Expression::Numeric(Number::Integer(x)) if *x >= 0 => std::char::from_u32(*x as u32),
+        _ => None,
+    };
+    match c {
+        Some(c) => Ok(Rc::new(Expression::Char(c))),
+        None => Err(format!(
+            "Expected Unicode scalar value argument to integer->char, got {}",
+            args[0]
+        )),
+    }
+}
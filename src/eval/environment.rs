@@ -0,0 +1,103 @@
+use crate::types::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::eval::io::default_ports;
+use crate::eval::list::expect_list;
+use crate::eval::{call, eval, standard_environment};
+
+fn new_environment(env: Environment) -> Rc<Expression> {
+    Rc::new(Expression::Environment(EnvSpec::Standalone(Rc::new(
+        RefCell::new(env),
+    ))))
+}
+
+//The report versions whose environments are supported, which both have the same bindings here
+fn expect_version(expr: &Expression, name: &str) -> Result<(), String> {
+    match expr {
+        Expression::Numeric(Number::Integer(5)) | Expression::Numeric(Number::Integer(7)) => Ok(()),
+        _ => Err(format!(
+            "Expected version 5 or 7 as argument to {}, got {}",
+            name, expr
+        )),
+    }
+}
+
+// (apply proc arg1 ... args) calls proc with the arguments before the last followed by the
+// elements of the last, which must be a list
+pub fn apply_procedure(
+    args: &[Rc<Expression>],
+    env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let (last, init) = args[1..].split_last().unwrap();
+    let mut call_args = init.to_vec();
+    call_args.extend(expect_list(last, "apply")?.iter().cloned());
+    call(&args[0], call_args, env)
+}
+
+// (eval expr env) evaluates a datum. Definitions it makes go into env, and it can't see the
+// local bindings of the procedure that called eval.
+pub fn eval_procedure(
+    args: &[Rc<Expression>],
+    env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let expr = Rc::clone(&args[0]);
+    match args[1].as_ref() {
+        Expression::Environment(EnvSpec::Interaction) => {
+            let locals = env.split_off(1);
+            let result = eval(expr, env);
+            env.extend(locals);
+            result
+        }
+        //An environment can't be lent out twice, so evaluating in one from code that is
+        //already running in it is an error
+        Expression::Environment(EnvSpec::Standalone(inner)) => match inner.try_borrow_mut() {
+            Ok(mut inner) => eval(expr, &mut inner),
+            Err(_) => Err(String::from(
+                "Cannot eval in an environment from code already running in it",
+            )),
+        },
+        other => Err(format!(
+            "Expected environment argument to eval, got {}",
+            other
+        )),
+    }
+}
+
+pub fn interaction_environment(
+    _args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    Ok(Rc::new(Expression::Environment(EnvSpec::Interaction)))
+}
+
+// (scheme-report-environment version) creates a fresh environment with the standard
+// procedures, so code evaluated in it can't change the program's own definitions
+pub fn scheme_report_environment(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    expect_version(&args[0], "scheme-report-environment")?;
+    Ok(new_environment(standard_environment()))
+}
+
+// (null-environment version) creates a fresh environment with no bindings, where only the
+// special forms are available. It still has current ports of its own, which programs reach
+// through procedures rather than names.
+pub fn null_environment(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    expect_version(&args[0], "null-environment")?;
+    Ok(new_environment(vec![default_ports().into_iter().collect()]))
+}
+
+pub fn is_environment(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    Ok(Rc::new(Expression::from(matches!(
+        args[0].as_ref(),
+        Expression::Environment(_)
+    ))))
+}
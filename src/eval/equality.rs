@@ -0,0 +1,43 @@
+use crate::types::*;
+use std::rc::Rc;
+
+fn compare(
+    args: &[Rc<Expression>],
+    test: fn(&Rc<Expression>, &Rc<Expression>) -> bool,
+) -> Result<Rc<Expression>, String> {
+    Ok(Rc::new(Expression::Boolean(test(&args[0], &args[1]))))
+}
+
+pub fn eq(args: &[Rc<Expression>], _env: &mut Environment) -> Result<Rc<Expression>, String> {
+    compare(args, Expression::is_eq)
+}
+
+pub fn eqv(args: &[Rc<Expression>], _env: &mut Environment) -> Result<Rc<Expression>, String> {
+    compare(args, |a, b| a.eqv(b))
+}
+
+pub fn equal(args: &[Rc<Expression>], _env: &mut Environment) -> Result<Rc<Expression>, String> {
+    compare(args, |a, b| a.equal(b))
+}
+
+pub fn string_equal(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let mut strings = Vec::with_capacity(args.len());
+    for arg in args.iter() {
+        match arg.as_ref() {
+            Expression::String(s) => strings.push(s),
+            expr => {
+                return Err(format!(
+                    "Expected string argument to string=?, got {}",
+                    expr
+                ))
+            }
+        }
+    }
+
+    Ok(Rc::new(Expression::Boolean(
+        strings.windows(2).all(|pair| pair[0] == pair[1]),
+    )))
+}
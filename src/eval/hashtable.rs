@@ -0,0 +1,214 @@
+use crate::types::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::eval::call;
+
+fn expect_table<'a>(expr: &'a Expression, name: &str) -> Result<&'a RefCell<HashTable>, String> {
+    match expr {
+        Expression::HashTable(table) => Ok(table),
+        _ => Err(format!(
+            "Expected hash table argument to {}, got {}",
+            name, expr
+        )),
+    }
+}
+
+//string=? tables can only hold string keys
+fn check_key(table: &HashTable, key: &Expression, name: &str) -> Result<(), String> {
+    match (table.equivalence(), key) {
+        (Equivalence::String, Expression::String(_)) => Ok(()),
+        (Equivalence::String, _) => Err(format!(
+            "Expected string key in {} for string=? table, got {}",
+            name, key
+        )),
+        _ => Ok(()),
+    }
+}
+
+// (make-hash-table [equivalence]) takes one of the procedures eq?, eqv?, equal? (the default)
+// or string=?
+pub fn make_hash_table(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let equivalence = match args.first().map(|arg| arg.as_ref()) {
+        None => Some(Equivalence::Equal),
+        Some(Expression::Builtin(builtin)) => Equivalence::from_name(builtin.get_name()),
+        Some(_) => None,
+    };
+
+    match equivalence {
+        Some(equivalence) => Ok(Rc::new(Expression::HashTable(RefCell::new(
+            HashTable::new(equivalence),
+        )))),
+        None => Err(format!(
+            "Expected one of eq?, eqv?, equal? or string=? in make-hash-table, got {}",
+            args[0]
+        )),
+    }
+}
+
+pub fn is_hash_table(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    Ok(Rc::new(Expression::Boolean(matches!(
+        args[0].as_ref(),
+        Expression::HashTable(_)
+    ))))
+}
+
+// (hash-table-ref table key [fail [succeed]]) calls fail with no arguments if the key is
+// missing, and passes the value to succeed if it is present
+pub fn hash_table_ref(
+    args: &[Rc<Expression>],
+    env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let table = expect_table(&args[0], "hash-table-ref")?;
+    check_key(&table.borrow(), &args[1], "hash-table-ref")?;
+    let value = table.borrow().get(&args[1]);
+
+    match (value, args.get(2), args.get(3)) {
+        (Some(value), _, Some(succeed)) => call(succeed, vec![value], env),
+        (Some(value), _, None) => Ok(value),
+        (None, Some(fail), _) => call(fail, Vec::new(), env),
+        (None, None, _) => Err(format!("Key not found in hash table: {}", args[1])),
+    }
+}
+
+pub fn hash_table_ref_default(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let table = expect_table(&args[0], "hash-table-ref/default")?.borrow();
+    check_key(&table, &args[1], "hash-table-ref/default")?;
+    Ok(table.get(&args[1]).unwrap_or_else(|| Rc::clone(&args[2])))
+}
+
+pub fn hash_table_set(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    //The key is checked before borrowing the table mutably, since printing it in an error
+    //borrows the table again if it's the table itself
+    let table = expect_table(&args[0], "hash-table-set!")?;
+    check_key(&table.borrow(), &args[1], "hash-table-set!")?;
+    table.borrow_mut().insert(&args[1], Rc::clone(&args[2]));
+    Ok(Rc::new(Expression::Unspecified))
+}
+
+pub fn hash_table_delete(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let table = expect_table(&args[0], "hash-table-delete!")?;
+    check_key(&table.borrow(), &args[1], "hash-table-delete!")?;
+    table.borrow_mut().remove(&args[1]);
+    Ok(Rc::new(Expression::Unspecified))
+}
+
+pub fn hash_table_contains(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let table = expect_table(&args[0], "hash-table-contains?")?.borrow();
+    check_key(&table, &args[1], "hash-table-contains?")?;
+    Ok(Rc::new(Expression::Boolean(table.get(&args[1]).is_some())))
+}
+
+// (hash-table-update! table key proc [fail]) replaces the value with the result of calling
+// proc on it, calling fail to get the initial value if the key is missing
+pub fn hash_table_update(
+    args: &[Rc<Expression>],
+    env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let table = expect_table(&args[0], "hash-table-update!")?;
+    check_key(&table.borrow(), &args[1], "hash-table-update!")?;
+
+    //The table isn't borrowed while calling back into the interpreter, since the
+    //procedures are free to modify it
+    let value = table.borrow().get(&args[1]);
+    let value = match (value, args.get(3)) {
+        (Some(value), _) => value,
+        (None, Some(fail)) => call(fail, Vec::new(), env)?,
+        (None, None) => return Err(format!("Key not found in hash table: {}", args[1])),
+    };
+
+    let value = call(&args[2], vec![value], env)?;
+    table.borrow_mut().insert(&args[1], value);
+    Ok(Rc::new(Expression::Unspecified))
+}
+
+pub fn hash_table_update_default(
+    args: &[Rc<Expression>],
+    env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let table = expect_table(&args[0], "hash-table-update!/default")?;
+    check_key(&table.borrow(), &args[1], "hash-table-update!/default")?;
+
+    let value = table.borrow().get(&args[1]);
+    let value = value.unwrap_or_else(|| Rc::clone(&args[3]));
+
+    let value = call(&args[2], vec![value], env)?;
+    table.borrow_mut().insert(&args[1], value);
+    Ok(Rc::new(Expression::Unspecified))
+}
+
+pub fn hash_table_count(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let len = expect_table(&args[0], "hash-table-count")?.borrow().len();
+    Ok(Rc::new(Expression::from(len as i32)))
+}
+
+pub fn hash_table_keys(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let keys = expect_table(&args[0], "hash-table-keys")?.borrow().keys();
+    Ok(Rc::new(Expression::SExpr(keys)))
+}
+
+pub fn hash_table_values(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let values = expect_table(&args[0], "hash-table-values")?
+        .borrow()
+        .values();
+    Ok(Rc::new(Expression::SExpr(values)))
+}
+
+// Calls proc with each key and value, in insertion order
+pub fn hash_table_walk(
+    args: &[Rc<Expression>],
+    env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let entries = expect_table(&args[0], "hash-table-walk")?
+        .borrow()
+        .entries();
+    for (key, value) in entries {
+        call(&args[1], vec![key, value], env)?;
+    }
+    Ok(Rc::new(Expression::Unspecified))
+}
+
+pub fn hash_table_clear(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    expect_table(&args[0], "hash-table-clear!")?
+        .borrow_mut()
+        .clear();
+    Ok(Rc::new(Expression::Unspecified))
+}
+
+pub fn hash_table_copy(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let table = expect_table(&args[0], "hash-table-copy")?.borrow().clone();
+    Ok(Rc::new(Expression::HashTable(RefCell::new(table))))
+}
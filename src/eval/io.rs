@@ -0,0 +1,443 @@
+use crate::eval::call;
+use crate::eval::vector::expect_range;
+use crate::port::Port;
+use crate::printer::{self, PrintOptions};
+use crate::types::*;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter};
+use std::path::Path;
+use std::rc::Rc;
+
+//The ports that input and output go to when no port is given are bound in the global frame,
+//so each environment has its own. Their names have spaces in them, so programs can't refer
+//to them directly.
+const CURRENT_INPUT: &str = "current input port";
+const CURRENT_OUTPUT: &str = "current output port";
+const CURRENT_ERROR: &str = "current error port";
+
+thread_local! {
+    //Standard input is read through one port, shared by every environment, so data left over
+    //on a line is kept for the next read
+    static STDIN: Rc<Expression> = Rc::new(Expression::Port(Port::interactive_input(
+        BufReader::new(io::stdin()),
+    )));
+}
+
+// The bindings for the current ports in a new global frame
+pub(super) fn default_ports() -> Vec<(String, Rc<Expression>)> {
+    vec![
+        (String::from(CURRENT_INPUT), STDIN.with(Rc::clone)),
+        (
+            String::from(CURRENT_OUTPUT),
+            Rc::new(Expression::Port(Port::output(io::stdout()))),
+        ),
+        (
+            String::from(CURRENT_ERROR),
+            Rc::new(Expression::Port(Port::output(io::stderr()))),
+        ),
+    ]
+}
+
+fn current(env: &Environment, param: &str) -> Rc<Expression> {
+    Rc::clone(&env[0][param])
+}
+
+//Calls func with param set to port, and sets it back afterwards even if func fails
+fn redirect<T, F: FnOnce(&mut Environment) -> T>(
+    env: &mut Environment,
+    param: &str,
+    port: Rc<Expression>,
+    func: F,
+) -> T {
+    let old = env[0].insert(String::from(param), port);
+    let result = func(env);
+    if let Some(old) = old {
+        env[0].insert(String::from(param), old);
+    }
+    result
+}
+
+fn expect_string<'a>(expr: &'a Expression, name: &str) -> Result<&'a str, String> {
+    match expr {
+        Expression::String(s) => Ok(s),
+        _ => Err(format!(
+            "Expected string argument to {}, got {}",
+            name, expr
+        )),
+    }
+}
+
+fn expect_port<'a>(expr: &'a Expression, name: &str) -> Result<&'a Port, String> {
+    match expr {
+        Expression::Port(port) => Ok(port),
+        _ => Err(format!("Expected port argument to {}, got {}", name, expr)),
+    }
+}
+
+fn expect_input_port<'a>(expr: &'a Expression, name: &str) -> Result<&'a Port, String> {
+    match expr {
+        Expression::Port(port) if port.is_input() => Ok(port),
+        _ => Err(format!(
+            "Expected input port argument to {}, got {}",
+            name, expr
+        )),
+    }
+}
+
+fn expect_output_port<'a>(expr: &'a Expression, name: &str) -> Result<&'a Port, String> {
+    match expr {
+        Expression::Port(port) if port.is_output() => Ok(port),
+        _ => Err(format!(
+            "Expected output port argument to {}, got {}",
+            name, expr
+        )),
+    }
+}
+
+//The optional port argument to input procedures, which defaults to the current input port
+fn input_port_arg(args: &[Rc<Expression>], idx: usize, env: &Environment) -> Rc<Expression> {
+    match args.get(idx) {
+        Some(port) => Rc::clone(port),
+        None => current(env, CURRENT_INPUT),
+    }
+}
+
+fn eof_or<T, F: FnOnce(T) -> Expression>(value: Option<T>, func: F) -> Rc<Expression> {
+    Rc::new(value.map_or(Expression::Eof, func))
+}
+
+//Writes to the optional port argument at args[idx], or to the current output port without one
+fn output(
+    args: &[Rc<Expression>],
+    idx: usize,
+    name: &str,
+    text: &str,
+    env: &Environment,
+) -> Result<Rc<Expression>, String> {
+    let port = match args.get(idx) {
+        Some(port) => Rc::clone(port),
+        None => current(env, CURRENT_OUTPUT),
+    };
+    expect_output_port(&port, name)?.write_str(text)?;
+    Ok(Rc::new(Expression::Unspecified))
+}
+
+// (write obj [port])
+pub fn write(args: &[Rc<Expression>], env: &mut Environment) -> Result<Rc<Expression>, String> {
+    output(args, 1, "write", &printer::write(&args[0]), env)
+}
+
+// (display obj [port])
+pub fn display(args: &[Rc<Expression>], env: &mut Environment) -> Result<Rc<Expression>, String> {
+    output(args, 1, "display", &printer::display(&args[0]), env)
+}
+
+// (newline [port])
+pub fn newline(args: &[Rc<Expression>], env: &mut Environment) -> Result<Rc<Expression>, String> {
+    output(args, 0, "newline", "\n", env)
+}
+
+// (write-string string [port [start [end]]])
+pub fn write_string(
+    args: &[Rc<Expression>],
+    env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let text = expect_string(&args[0], "write-string")?;
+    let chars = text.chars().collect::<Vec<_>>();
+    let (start, end) = expect_range(args, 2, chars.len(), "write-string")?;
+    let text = chars[start..end].iter().collect::<String>();
+    output(args, 1, "write-string", &text, env)
+}
+
+// (write-char char [port])
+pub fn write_char(
+    args: &[Rc<Expression>],
+    env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    match args[0].as_ref() {
+        Expression::Char(c) => output(args, 1, "write-char", &c.to_string(), env),
+        expr => Err(format!(
+            "Expected char argument to write-char, got {}",
+            expr
+        )),
+    }
+}
+
+fn limit_arg(args: &[Rc<Expression>], idx: usize) -> Result<Option<usize>, String> {
+    match args.get(idx).map(|arg| arg.as_ref()) {
+        None | Some(Expression::Boolean(false)) => Ok(None),
+        Some(Expression::Numeric(Number::Integer(n))) if *n >= 0 => Ok(Some(*n as usize)),
+        Some(expr) => Err(format!(
+            "Expected non-negative integer or #f argument to pretty-print, got {}",
+            expr
+        )),
+    }
+}
+
+// (pretty-print obj [width [max-depth [max-length]]]) where #f leaves a limit off
+pub fn pretty_print(
+    args: &[Rc<Expression>],
+    env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let mut options = PrintOptions::default();
+    if let Some(width) = limit_arg(args, 1)? {
+        options.width = width;
+    }
+    options.max_depth = limit_arg(args, 2)?;
+    options.max_length = limit_arg(args, 3)?;
+
+    let mut text = printer::pretty_print(&args[0], &options);
+    text.push('\n');
+    output(args, 4, "pretty-print", &text, env)
+}
+
+// (read [port]) reads the next datum, or returns the eof object at the end of the input
+pub fn read(args: &[Rc<Expression>], env: &mut Environment) -> Result<Rc<Expression>, String> {
+    let port = input_port_arg(args, 0, env);
+    let datum = expect_input_port(&port, "read")?.read()?;
+    Ok(datum.unwrap_or_else(|| Rc::new(Expression::Eof)))
+}
+
+pub fn read_line(args: &[Rc<Expression>], env: &mut Environment) -> Result<Rc<Expression>, String> {
+    let port = input_port_arg(args, 0, env);
+    let line = expect_input_port(&port, "read-line")?.read_line()?;
+    Ok(eof_or(line, Expression::String))
+}
+
+pub fn read_char(args: &[Rc<Expression>], env: &mut Environment) -> Result<Rc<Expression>, String> {
+    let port = input_port_arg(args, 0, env);
+    let c = expect_input_port(&port, "read-char")?.read_char()?;
+    Ok(eof_or(c, Expression::Char))
+}
+
+pub fn peek_char(args: &[Rc<Expression>], env: &mut Environment) -> Result<Rc<Expression>, String> {
+    let port = input_port_arg(args, 0, env);
+    let c = expect_input_port(&port, "peek-char")?.peek_char()?;
+    Ok(eof_or(c, Expression::Char))
+}
+
+pub fn char_ready(
+    args: &[Rc<Expression>],
+    env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let port = input_port_arg(args, 0, env);
+    let ready = expect_input_port(&port, "char-ready?")?.char_ready()?;
+    Ok(Rc::new(Expression::from(ready)))
+}
+
+//(param) returns the port, and (param port) redirects to it from then on
+fn parameter(
+    env: &mut Environment,
+    param: &str,
+    args: &[Rc<Expression>],
+    input: bool,
+    name: &str,
+) -> Result<Rc<Expression>, String> {
+    match args.first() {
+        None => Ok(current(env, param)),
+        Some(port) => {
+            if input {
+                expect_input_port(port, name)?;
+            } else {
+                expect_output_port(port, name)?;
+            }
+            env[0].insert(String::from(param), Rc::clone(port));
+            Ok(Rc::new(Expression::Unspecified))
+        }
+    }
+}
+
+// (current-input-port [port])
+pub fn current_input_port(
+    args: &[Rc<Expression>],
+    env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    parameter(env, CURRENT_INPUT, args, true, "current-input-port")
+}
+
+// (current-output-port [port])
+pub fn current_output_port(
+    args: &[Rc<Expression>],
+    env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    parameter(env, CURRENT_OUTPUT, args, false, "current-output-port")
+}
+
+// (current-error-port [port])
+pub fn current_error_port(
+    args: &[Rc<Expression>],
+    env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    parameter(env, CURRENT_ERROR, args, false, "current-error-port")
+}
+
+// (flush-output-port [port]) writes out anything buffered for a file
+pub fn flush_output_port(
+    args: &[Rc<Expression>],
+    env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let port = match args.first() {
+        Some(port) => Rc::clone(port),
+        None => current(env, CURRENT_OUTPUT),
+    };
+    expect_output_port(&port, "flush-output-port")?.flush()?;
+    Ok(Rc::new(Expression::Unspecified))
+}
+
+pub fn open_input_string(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let text = expect_string(&args[0], "open-input-string")?;
+    Ok(Rc::new(Expression::Port(Port::string_input(text))))
+}
+
+pub fn open_output_string(
+    _args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    Ok(Rc::new(Expression::Port(Port::string_output())))
+}
+
+pub fn get_output_string(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    match args[0].as_ref() {
+        Expression::Port(port) => match port.get_output_string() {
+            Some(text) => Ok(Rc::new(Expression::String(text))),
+            None => Err(format!(
+                "Expected string output port argument to get-output-string, got {}",
+                args[0]
+            )),
+        },
+        expr => Err(format!(
+            "Expected string output port argument to get-output-string, got {}",
+            expr
+        )),
+    }
+}
+
+pub fn is_port(args: &[Rc<Expression>], _env: &mut Environment) -> Result<Rc<Expression>, String> {
+    Ok(Rc::new(Expression::from(matches!(
+        args[0].as_ref(),
+        Expression::Port(_)
+    ))))
+}
+
+pub fn is_input_port(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    Ok(Rc::new(Expression::from(matches!(
+        args[0].as_ref(),
+        Expression::Port(port) if port.is_input()
+    ))))
+}
+
+pub fn is_output_port(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    Ok(Rc::new(Expression::from(matches!(
+        args[0].as_ref(),
+        Expression::Port(port) if port.is_output()
+    ))))
+}
+
+pub fn eof_object(
+    _args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    Ok(Rc::new(Expression::Eof))
+}
+
+pub fn is_eof_object(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    Ok(Rc::new(Expression::from(matches!(
+        args[0].as_ref(),
+        Expression::Eof
+    ))))
+}
+
+fn open_input(path: &str) -> Result<Rc<Expression>, String> {
+    let file = File::open(path).map_err(|err| format!("Could not open {}: {}", path, err))?;
+    Ok(Rc::new(Expression::Port(Port::input(BufReader::new(file)))))
+}
+
+fn open_output(path: &str) -> Result<Rc<Expression>, String> {
+    let file = File::create(path).map_err(|err| format!("Could not open {}: {}", path, err))?;
+    Ok(Rc::new(Expression::Port(Port::output(BufWriter::new(
+        file,
+    )))))
+}
+
+pub fn open_input_file(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    open_input(expect_string(&args[0], "open-input-file")?)
+}
+
+// (open-output-file filename) creates the file, or truncates it if it exists
+pub fn open_output_file(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    open_output(expect_string(&args[0], "open-output-file")?)
+}
+
+// (call-with-output-file filename proc) calls proc with a port writing to the file, and
+// closes it when proc returns
+pub fn call_with_output_file(
+    args: &[Rc<Expression>],
+    env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let port = open_output(expect_string(&args[0], "call-with-output-file")?)?;
+    let result = call(&args[1], vec![Rc::clone(&port)], env);
+    let closed = expect_port(&port, "call-with-output-file")?.close();
+    let value = result?;
+    closed?;
+    Ok(value)
+}
+
+// (with-input-from-file filename thunk) calls thunk with the current input port reading
+// from the file
+pub fn with_input_from_file(
+    args: &[Rc<Expression>],
+    env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let port = open_input(expect_string(&args[0], "with-input-from-file")?)?;
+    let result = redirect(env, CURRENT_INPUT, Rc::clone(&port), |env| {
+        call(&args[1], Vec::new(), env)
+    });
+    expect_port(&port, "with-input-from-file")?.close()?;
+    result
+}
+
+pub fn close_port(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    expect_port(&args[0], "close-port")?.close()?;
+    Ok(Rc::new(Expression::Unspecified))
+}
+
+pub fn file_exists(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let path = expect_string(&args[0], "file-exists?")?;
+    Ok(Rc::new(Expression::from(Path::new(path).exists())))
+}
+
+pub fn delete_file(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let path = expect_string(&args[0], "delete-file")?;
+    fs::remove_file(path).map_err(|err| format!("Could not delete {}: {}", path, err))?;
+    Ok(Rc::new(Expression::Unspecified))
+}
@@ -0,0 +1,248 @@
+use crate::types::*;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::fs;
+use std::rc::Rc;
+
+use serde::Serialize;
+use serde_json::ser::PrettyFormatter;
+use serde_json::{Map, Value};
+
+//How JSON objects are read: as equal? hash tables keyed by strings, or as lists of
+//(key value) pairs
+#[derive(Clone, Copy)]
+enum ObjectType {
+    HashTable,
+    Alist,
+}
+
+fn expect_string<'a>(expr: &'a Expression, name: &str) -> Result<&'a str, String> {
+    match expr {
+        Expression::String(s) => Ok(s),
+        _ => Err(format!(
+            "Expected string argument to {}, got {}",
+            name, expr
+        )),
+    }
+}
+
+fn object_type_arg(args: &[Rc<Expression>], idx: usize, name: &str) -> Result<ObjectType, String> {
+    match args.get(idx).map(|arg| arg.as_ref()) {
+        None => Ok(ObjectType::HashTable),
+        Some(Expression::Identifier(id)) if id == "hash-table" => Ok(ObjectType::HashTable),
+        Some(Expression::Identifier(id)) if id == "alist" => Ok(ObjectType::Alist),
+        Some(expr) => Err(format!(
+            "Expected hash-table or alist as object type in {}, got {}",
+            name, expr
+        )),
+    }
+}
+
+//#f writes compact JSON, #t indents by two spaces, and an integer indents by that many
+fn indent_arg(args: &[Rc<Expression>], idx: usize, name: &str) -> Result<Option<usize>, String> {
+    match args.get(idx).map(|arg| arg.as_ref()) {
+        None | Some(Expression::Boolean(false)) => Ok(None),
+        Some(Expression::Boolean(true)) => Ok(Some(2)),
+        Some(Expression::Numeric(Number::Integer(n))) if *n >= 0 => Ok(Some(*n as usize)),
+        Some(expr) => Err(format!(
+            "Expected #t, #f or an indent width in {}, got {}",
+            name, expr
+        )),
+    }
+}
+
+//Integers outside the range of integers here, and reals that need more than single precision,
+//are an error rather than being rounded
+fn from_json(value: Value, object_type: ObjectType) -> Result<Rc<Expression>, String> {
+    let expr = match value {
+        //JSON has no other value that null could stand for, so it reads as the symbol null
+        Value::Null => Expression::Identifier(String::from("null")),
+        Value::Bool(b) => Expression::Boolean(b),
+        Value::Number(n) if n.is_f64() => match n.as_f64().and_then(Number::float_from_f64) {
+            Some(x) => Expression::from(x),
+            None => return Err(format!("JSON real {} needs more than single precision", n)),
+        },
+        Value::Number(n) => match n.as_i64().and_then(|x| i32::try_from(x).ok()) {
+            Some(x) => Expression::from(x),
+            None => return Err(format!("JSON integer {} is out of range", n)),
+        },
+        Value::String(s) => Expression::String(s),
+        Value::Array(values) => Expression::Vector(RefCell::new(
+            values
+                .into_iter()
+                .map(|value| from_json(value, object_type))
+                .collect::<Result<_, _>>()?,
+        )),
+        Value::Object(map) => {
+            let entries = map
+                .into_iter()
+                .map(|(k, v)| Ok((Rc::new(Expression::String(k)), from_json(v, object_type)?)))
+                .collect::<Result<Vec<_>, String>>()?
+                .into_iter();
+            match object_type {
+                ObjectType::HashTable => {
+                    let mut table = HashTable::new(Equivalence::Equal);
+                    for (k, v) in entries {
+                        table.insert(&k, v);
+                    }
+                    Expression::HashTable(RefCell::new(table))
+                }
+                ObjectType::Alist => Expression::SExpr(
+                    entries
+                        .map(|(k, v)| Rc::new(Expression::SExpr(vec![k, v])))
+                        .collect(),
+                ),
+            }
+        }
+    };
+    Ok(Rc::new(expr))
+}
+
+//Object keys may be strings or symbols
+fn json_key(key: &Expression) -> Result<String, String> {
+    match key {
+        Expression::String(s) | Expression::Identifier(s) => Ok(s.clone()),
+        _ => Err(format!(
+            "Expected string or symbol as JSON object key, got {}",
+            key
+        )),
+    }
+}
+
+//Vectors become arrays, and hash tables, records and lists of (key value) pairs become
+//objects. Values that contain themselves have no JSON form, so the values being converted are
+//tracked to catch them.
+fn to_json(expr: &Expression, path: &mut HashSet<*const Expression>) -> Result<Value, String> {
+    let compound = matches!(
+        expr,
+        Expression::Vector(_)
+            | Expression::HashTable(_)
+            | Expression::Record(_)
+            | Expression::SExpr(_)
+    );
+    if compound && !path.insert(expr) {
+        return Err(String::from(
+            "Cannot represent a value that contains itself in JSON",
+        ));
+    }
+
+    let result = match expr {
+        Expression::Identifier(id) if id == "null" => Ok(Value::Null),
+        Expression::Identifier(id) => Ok(Value::String(id.clone())),
+        Expression::Boolean(b) => Ok(Value::Bool(*b)),
+        Expression::Numeric(Number::Integer(x)) => Ok(Value::from(*x)),
+        Expression::Numeric(Number::Float(x)) => {
+            serde_json::Number::from_f64(Number::float_to_f64(*x))
+                .map(Value::Number)
+                .ok_or_else(|| format!("Cannot represent {} in JSON", expr))
+        }
+        Expression::String(s) => Ok(Value::String(s.clone())),
+        Expression::Vector(v) => v
+            .borrow()
+            .iter()
+            .map(|expr| to_json(expr, path))
+            .collect::<Result<Vec<_>, String>>()
+            .map(Value::Array),
+        Expression::HashTable(table) => {
+            let mut map = Map::new();
+            for (k, v) in table.borrow().entries() {
+                map.insert(json_key(&k)?, to_json(&v, path)?);
+            }
+            Ok(Value::Object(map))
+        }
+        Expression::Record(record) => {
+            let mut map = Map::new();
+            for (field, value) in record.get_type().get_fields().iter().zip(record.values()) {
+                map.insert(field.clone(), to_json(&value, path)?);
+            }
+            Ok(Value::Object(map))
+        }
+        Expression::SExpr(list) => {
+            let mut map = Map::new();
+            for pair in list.iter() {
+                match pair.as_ref() {
+                    Expression::SExpr(pair) if pair.len() == 2 => {
+                        map.insert(json_key(&pair[0])?, to_json(&pair[1], path)?);
+                    }
+                    _ => {
+                        return Err(format!(
+                            "Expected (key value) pair in JSON object, got {}",
+                            pair
+                        ))
+                    }
+                }
+            }
+            Ok(Value::Object(map))
+        }
+        _ => Err(format!("Cannot represent {} in JSON", expr.type_name())),
+    };
+
+    path.remove(&(expr as *const Expression));
+    result
+}
+
+fn write_json(expr: &Expression, indent: Option<usize>) -> Result<String, String> {
+    let value = to_json(expr, &mut HashSet::new())?;
+    match indent {
+        None => Ok(value.to_string()),
+        Some(width) => {
+            let indent = " ".repeat(width);
+            let mut out = Vec::new();
+            let formatter = PrettyFormatter::with_indent(indent.as_bytes());
+            let mut serializer = serde_json::Serializer::with_formatter(&mut out, formatter);
+            value
+                .serialize(&mut serializer)
+                .map_err(|err| err.to_string())?;
+            Ok(String::from_utf8(out).unwrap())
+        }
+    }
+}
+
+// (json->scheme text [object-type]) where object-type is hash-table (the default) or alist.
+// Errors give the line and column of the malformed input.
+pub fn json_to_scheme(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let text = expect_string(&args[0], "json->scheme")?;
+    let object_type = object_type_arg(args, 1, "json->scheme")?;
+    let value = serde_json::from_str(text).map_err(|err| format!("Invalid JSON: {}", err))?;
+    from_json(value, object_type)
+}
+
+// (json-read filename [object-type])
+pub fn json_read(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let path = expect_string(&args[0], "json-read")?;
+    let object_type = object_type_arg(args, 1, "json-read")?;
+    let text =
+        fs::read_to_string(path).map_err(|err| format!("Could not read {}: {}", path, err))?;
+    let value =
+        serde_json::from_str(&text).map_err(|err| format!("Invalid JSON in {}: {}", path, err))?;
+    from_json(value, object_type)
+}
+
+// (scheme->json value [indent])
+pub fn scheme_to_json(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let indent = indent_arg(args, 1, "scheme->json")?;
+    Ok(Rc::new(Expression::String(write_json(&args[0], indent)?)))
+}
+
+// (json-write value filename [indent])
+pub fn json_write(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let path = expect_string(&args[1], "json-write")?;
+    let indent = indent_arg(args, 2, "json-write")?;
+    let mut text = write_json(&args[0], indent)?;
+    text.push('\n');
+    fs::write(path, text).map_err(|err| format!("Could not write {}: {}", path, err))?;
+    Ok(Rc::new(Expression::Unspecified))
+}
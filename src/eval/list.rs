@@ -0,0 +1,93 @@
+use crate::types::*;
+use std::rc::Rc;
+
+//Lists are stored as vectors of their elements, so there are no pairs and no improper lists.
+//The empty list is a list with no elements.
+fn new_list(elements: Vec<Rc<Expression>>) -> Rc<Expression> {
+    Rc::new(Expression::SExpr(elements))
+}
+
+pub(super) fn expect_list<'a>(
+    expr: &'a Expression,
+    name: &str,
+) -> Result<&'a [Rc<Expression>], String> {
+    match expr {
+        Expression::SExpr(list) => Ok(list),
+        _ => Err(format!("Expected list argument to {}, got {}", name, expr)),
+    }
+}
+
+fn expect_pair<'a>(expr: &'a Expression, name: &str) -> Result<&'a [Rc<Expression>], String> {
+    match expr {
+        Expression::SExpr(list) if !list.is_empty() => Ok(list),
+        _ => Err(format!(
+            "Expected non-empty list argument to {}, got {}",
+            name, expr
+        )),
+    }
+}
+
+pub fn is_null(args: &[Rc<Expression>], _env: &mut Environment) -> Result<Rc<Expression>, String> {
+    Ok(Rc::new(Expression::from(
+        matches!(args[0].as_ref(), Expression::SExpr(list) if list.is_empty()),
+    )))
+}
+
+pub fn is_pair(args: &[Rc<Expression>], _env: &mut Environment) -> Result<Rc<Expression>, String> {
+    Ok(Rc::new(Expression::from(
+        matches!(args[0].as_ref(), Expression::SExpr(list) if !list.is_empty()),
+    )))
+}
+
+pub fn is_list(args: &[Rc<Expression>], _env: &mut Environment) -> Result<Rc<Expression>, String> {
+    Ok(Rc::new(Expression::from(matches!(
+        args[0].as_ref(),
+        Expression::SExpr(_)
+    ))))
+}
+
+pub fn list(args: &[Rc<Expression>], _env: &mut Environment) -> Result<Rc<Expression>, String> {
+    Ok(new_list(args.to_vec()))
+}
+
+//The second argument has to be a list, since there are no improper lists
+pub fn cons(args: &[Rc<Expression>], _env: &mut Environment) -> Result<Rc<Expression>, String> {
+    match args[1].as_ref() {
+        Expression::SExpr(list) => {
+            let mut elements = Vec::with_capacity(list.len() + 1);
+            elements.push(Rc::clone(&args[0]));
+            elements.extend(list.iter().cloned());
+            Ok(new_list(elements))
+        }
+        expr => Err(format!(
+            "Expected list as second argument to cons, got {} (improper lists aren't supported)",
+            expr
+        )),
+    }
+}
+
+pub fn car(args: &[Rc<Expression>], _env: &mut Environment) -> Result<Rc<Expression>, String> {
+    Ok(Rc::clone(&expect_pair(&args[0], "car")?[0]))
+}
+
+pub fn cdr(args: &[Rc<Expression>], _env: &mut Environment) -> Result<Rc<Expression>, String> {
+    Ok(new_list(expect_pair(&args[0], "cdr")?[1..].to_vec()))
+}
+
+pub fn length(args: &[Rc<Expression>], _env: &mut Environment) -> Result<Rc<Expression>, String> {
+    let len = expect_list(&args[0], "length")?.len();
+    Ok(Rc::new(Expression::from(len as i32)))
+}
+
+pub fn append(args: &[Rc<Expression>], _env: &mut Environment) -> Result<Rc<Expression>, String> {
+    let mut elements = Vec::new();
+    for arg in args.iter() {
+        elements.extend(expect_list(arg, "append")?.iter().cloned());
+    }
+    Ok(new_list(elements))
+}
+
+pub fn reverse(args: &[Rc<Expression>], _env: &mut Environment) -> Result<Rc<Expression>, String> {
+    let list = expect_list(&args[0], "reverse")?;
+    Ok(new_list(list.iter().rev().cloned().collect()))
+}
@@ -1,48 +1,263 @@
+use crate::parse::parse_str;
 use crate::types::*;
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
 use std::rc::Rc;
 
 use lazy_static::lazy_static;
 
 mod arithmetic;
+mod char;
+mod environment;
+mod equality;
+mod hashtable;
+mod io;
+mod json;
+mod list;
+mod record;
+mod vector;
 
 use self::arithmetic::*;
+use self::char::*;
+use self::environment::*;
+use self::equality::*;
+use self::hashtable::*;
+use self::io::*;
+use self::json::*;
+use self::list::*;
+use self::record::*;
+use self::vector::*;
 
 lazy_static! {
+    //Syntax that needs its arguments unevaluated. Everything else is an ordinary procedure
+    //bound in the global environment.
     static ref SPECIAL_FORMS: HashSet<&'static str> = [
-        "+",
-        "-",
-        "*",
-        "/",
         "define",
+        "define-record-type",
         "cond",
+        "case",
+        "when",
+        "unless",
+        "quote",
         "if",
-        "<",
-        "=",
         "and",
-        "or",
-        "remainder"
+        "or"
     ]
     .iter()
     .cloned()
     .collect();
 }
 
+//Procedures implemented in Rust, bound in the global environment
+const PRIMITIVES: &[(&str, Arity, BuiltinFn)] = &[
+    ("+", Arity::AtLeast(2), add),
+    ("-", Arity::AtLeast(2), sub),
+    ("*", Arity::AtLeast(2), mul),
+    ("/", Arity::AtLeast(2), div),
+    ("<", Arity::Exactly(2), less_than),
+    ("=", Arity::Exactly(2), equal_to),
+    ("remainder", Arity::Exactly(2), remainder),
+    ("eq?", Arity::Exactly(2), eq),
+    ("eqv?", Arity::Exactly(2), eqv),
+    ("equal?", Arity::Exactly(2), equal),
+    ("string=?", Arity::AtLeast(1), string_equal),
+    ("number->string", Arity::Between(1, 2), number_to_string),
+    ("string->number", Arity::Between(1, 2), string_to_number),
+    ("null?", Arity::Exactly(1), is_null),
+    ("pair?", Arity::Exactly(1), is_pair),
+    ("list?", Arity::Exactly(1), is_list),
+    ("list", Arity::AtLeast(0), list),
+    ("cons", Arity::Exactly(2), cons),
+    ("car", Arity::Exactly(1), car),
+    ("cdr", Arity::Exactly(1), cdr),
+    ("length", Arity::Exactly(1), length),
+    ("append", Arity::AtLeast(0), append),
+    ("reverse", Arity::Exactly(1), reverse),
+    ("vector?", Arity::Exactly(1), is_vector),
+    ("make-vector", Arity::Between(1, 2), make_vector),
+    ("vector", Arity::AtLeast(0), vector),
+    ("vector-length", Arity::Exactly(1), vector_length),
+    ("vector-ref", Arity::Exactly(2), vector_ref),
+    ("vector-set!", Arity::Exactly(3), vector_set),
+    ("vector->list", Arity::Between(1, 3), vector_to_list),
+    ("list->vector", Arity::Exactly(1), list_to_vector),
+    ("vector-fill!", Arity::Between(2, 4), vector_fill),
+    ("vector-copy", Arity::Between(1, 3), vector_copy),
+    ("vector-map", Arity::AtLeast(2), vector_map),
+    ("vector-for-each", Arity::AtLeast(2), vector_for_each),
+    ("bytevector?", Arity::Exactly(1), is_bytevector),
+    ("make-bytevector", Arity::Between(1, 2), make_bytevector),
+    ("bytevector", Arity::AtLeast(0), bytevector),
+    ("bytevector-length", Arity::Exactly(1), bytevector_length),
+    ("bytevector-u8-ref", Arity::Exactly(2), bytevector_u8_ref),
+    ("bytevector-u8-set!", Arity::Exactly(3), bytevector_u8_set),
+    ("bytevector-copy", Arity::Between(1, 3), bytevector_copy),
+    ("bytevector-append", Arity::AtLeast(0), bytevector_append),
+    ("make-hash-table", Arity::Between(0, 1), make_hash_table),
+    ("hash-table?", Arity::Exactly(1), is_hash_table),
+    ("hash-table-ref", Arity::Between(2, 4), hash_table_ref),
+    (
+        "hash-table-ref/default",
+        Arity::Exactly(3),
+        hash_table_ref_default,
+    ),
+    ("hash-table-set!", Arity::Exactly(3), hash_table_set),
+    ("hash-table-delete!", Arity::Exactly(2), hash_table_delete),
+    (
+        "hash-table-contains?",
+        Arity::Exactly(2),
+        hash_table_contains,
+    ),
+    (
+        "hash-table-update!",
+        Arity::Between(3, 4),
+        hash_table_update,
+    ),
+    (
+        "hash-table-update!/default",
+        Arity::Exactly(4),
+        hash_table_update_default,
+    ),
+    ("hash-table-count", Arity::Exactly(1), hash_table_count),
+    ("hash-table-keys", Arity::Exactly(1), hash_table_keys),
+    ("hash-table-values", Arity::Exactly(1), hash_table_values),
+    ("hash-table-walk", Arity::Exactly(2), hash_table_walk),
+    ("hash-table-clear!", Arity::Exactly(1), hash_table_clear),
+    ("hash-table-copy", Arity::Exactly(1), hash_table_copy),
+    ("char?", Arity::Exactly(1), is_char),
+    ("char->integer", Arity::Exactly(1), char_to_integer),
+    ("integer->char", Arity::Exactly(1), integer_to_char),
+    ("write", Arity::Between(1, 2), write),
+    ("display", Arity::Between(1, 2), display),
+    ("newline", Arity::Between(0, 1), newline),
+    ("write-string", Arity::Between(1, 4), write_string),
+    ("write-char", Arity::Between(1, 2), write_char),
+    ("pretty-print", Arity::Between(1, 5), pretty_print),
+    ("read", Arity::Between(0, 1), read),
+    ("read-line", Arity::Between(0, 1), read_line),
+    ("read-char", Arity::Between(0, 1), read_char),
+    ("peek-char", Arity::Between(0, 1), peek_char),
+    ("char-ready?", Arity::Between(0, 1), char_ready),
+    (
+        "current-input-port",
+        Arity::Between(0, 1),
+        current_input_port,
+    ),
+    (
+        "current-output-port",
+        Arity::Between(0, 1),
+        current_output_port,
+    ),
+    (
+        "current-error-port",
+        Arity::Between(0, 1),
+        current_error_port,
+    ),
+    ("flush-output-port", Arity::Between(0, 1), flush_output_port),
+    ("open-input-string", Arity::Exactly(1), open_input_string),
+    ("open-output-string", Arity::Exactly(0), open_output_string),
+    ("get-output-string", Arity::Exactly(1), get_output_string),
+    ("port?", Arity::Exactly(1), is_port),
+    ("input-port?", Arity::Exactly(1), is_input_port),
+    ("output-port?", Arity::Exactly(1), is_output_port),
+    ("open-input-file", Arity::Exactly(1), open_input_file),
+    ("open-output-file", Arity::Exactly(1), open_output_file),
+    (
+        "call-with-output-file",
+        Arity::Exactly(2),
+        call_with_output_file,
+    ),
+    (
+        "with-input-from-file",
+        Arity::Exactly(2),
+        with_input_from_file,
+    ),
+    ("close-port", Arity::Exactly(1), close_port),
+    ("file-exists?", Arity::Exactly(1), file_exists),
+    ("delete-file", Arity::Exactly(1), delete_file),
+    ("apply", Arity::AtLeast(2), apply_procedure),
+    ("eval", Arity::Exactly(2), eval_procedure),
+    (
+        "interaction-environment",
+        Arity::Exactly(0),
+        interaction_environment,
+    ),
+    (
+        "scheme-report-environment",
+        Arity::Exactly(1),
+        scheme_report_environment,
+    ),
+    ("null-environment", Arity::Exactly(1), null_environment),
+    ("environment?", Arity::Exactly(1), is_environment),
+    ("eof-object", Arity::Exactly(0), eof_object),
+    ("eof-object?", Arity::Exactly(1), is_eof_object),
+    ("json->scheme", Arity::Between(1, 2), json_to_scheme),
+    ("json-read", Arity::Between(1, 2), json_read),
+    ("scheme->json", Arity::Between(1, 2), scheme_to_json),
+    ("json-write", Arity::Between(2, 3), json_write),
+];
+
+//Creates an environment with a single global frame containing the primitives
+pub fn global_environment() -> Environment {
+    let frame = PRIMITIVES
+        .iter()
+        .map(|(name, arity, func)| {
+            (
+                String::from(*name),
+                Rc::new(Expression::Builtin(Builtin::new(name, *arity, *func))),
+            )
+        })
+        .chain(default_ports())
+        .collect();
+
+    vec![frame]
+}
+
+//The global environment with the standard library loaded into it
+pub fn standard_environment() -> Environment {
+    let mut env = global_environment();
+    let result = parse_str(include_str!("../stdlib.scm")).and_then(|exprs| {
+        exprs
+            .into_iter()
+            .try_for_each(|expr| eval(expr, &mut env).map(|_| ()))
+    });
+    if let Err(msg) = result {
+        panic!("Error loading standard library: {}", msg);
+    }
+    env
+}
+
+//Everything other than symbols and non-empty lists evaluates to itself. That includes
+//procedures and other values with no written form, which eval can be handed directly, as in
+//(eval (list + 1 2) (interaction-environment)).
 pub fn eval(expr: Rc<Expression>, env: &mut Environment) -> Result<Rc<Expression>, String> {
     match expr.as_ref() {
-        Expression::Numeric(_) | Expression::Boolean(_) => Ok(Rc::clone(&expr)),
+        Expression::Numeric(_)
+        | Expression::Boolean(_)
+        | Expression::Char(_)
+        | Expression::String(_)
+        | Expression::Vector(_)
+        | Expression::Bytevector(_)
+        | Expression::HashTable(_)
+        | Expression::Procedure(_)
+        | Expression::Builtin(_)
+        | Expression::RecordType(_)
+        | Expression::Record(_)
+        | Expression::RecordProcedure(_)
+        | Expression::Port(_)
+        | Expression::Environment(_)
+        | Expression::Eof
+        | Expression::Unspecified => Ok(Rc::clone(&expr)),
+        //The empty list evaluates to itself rather than being an empty application
+        Expression::SExpr(list) if list.is_empty() => Ok(Rc::clone(&expr)),
         Expression::Identifier(id) => match env_lookup(id, env) {
             Some(expr) => Ok(Rc::clone(&expr)),
             None => Err(format!("Unbound variable: {}", id)),
         },
         Expression::SExpr(list) => apply(list, env),
-        _ => {
-            panic!("Should never evaluate a procedure!");
-        }
     }
 }
 
-fn apply(list: &Vec<Rc<Expression>>, env: &mut Environment) -> Result<Rc<Expression>, String> {
+fn apply(list: &[Rc<Expression>], env: &mut Environment) -> Result<Rc<Expression>, String> {
     if list.is_empty() {
         return Err(String::from("Empty application"));
     }
@@ -50,7 +265,8 @@ fn apply(list: &Vec<Rc<Expression>>, env: &mut Environment) -> Result<Rc<Express
     //Try to evaluate the first sub-expression
     let result = eval(Rc::clone(&list[0]), env);
 
-    //If evaluation fails, check for a special form
+    //If evaluation fails, check for a special form. Since special forms are only tried for
+    //unbound identifiers, user definitions shadow them.
     if result.is_err() {
         if let Expression::Identifier(id) = list[0].as_ref() {
             if let Some(spec_result) = special_form(id, &list[1..], env) {
@@ -62,21 +278,25 @@ fn apply(list: &Vec<Rc<Expression>>, env: &mut Environment) -> Result<Rc<Express
         }
     }
 
-    assert!(result.is_ok());
+    //Evaluation succeeded, evaluate the arguments and call the procedure
+    let proc = result?;
+    let args = eval_args(&list[1..], env)?;
 
-    //Evaluation succeeded, try to call procedure
-    //Get arguments from list of identifiers
-    let args = &list[1..];
+    call(&proc, args, env)
+}
 
-    match result.unwrap().as_ref() {
+//Calls an evaluated procedure with a list of evaluated arguments
+fn call(
+    proc: &Expression,
+    args: Vec<Rc<Expression>>,
+    env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    match proc {
         Expression::Procedure(proc) => {
             //Check that arity matches provided args
             if proc.arity() == args.len() {
-                //Create new stack frame, fill in evaluated args
-                let mut frame = HashMap::with_capacity(args.len());
-                for (key, arg) in proc.get_arg_ids().iter().zip(args.iter()) {
-                    frame.insert(key.clone(), eval(Rc::clone(arg), env)?);
-                }
+                //Create new stack frame, fill in args
+                let frame = proc.get_arg_ids().iter().cloned().zip(args).collect();
 
                 //Push the frame, evaluate procedure
                 env.push(frame);
@@ -93,17 +313,25 @@ fn apply(list: &Vec<Rc<Expression>>, env: &mut Environment) -> Result<Rc<Express
                 ))
             }
         }
+        Expression::Builtin(builtin) => builtin.call(&args, env),
+        Expression::RecordProcedure(proc) => call_record_procedure(proc, args),
         Expression::Numeric(num) => Err(format!("Cannot apply Number {} as a Procedure.", num)),
         Expression::Boolean(b) => Err(format!("Cannot apply boolean {} as a Procedure.", b)),
-        //Eval should never return an Identifier or S-Expression!
-        Expression::Identifier(_) => unreachable!(),
-        Expression::SExpr(_) => unreachable!(),
+        _ => Err(format!("Cannot apply {} as a Procedure.", proc)),
     }
 }
 
-fn env_lookup(key: &String, env: &Environment) -> Option<Rc<Expression>> {
+//Evaluates each argument of a procedure in order
+fn eval_args(
+    args: &[Rc<Expression>],
+    env: &mut Environment,
+) -> Result<Vec<Rc<Expression>>, String> {
+    args.iter().map(|arg| eval(Rc::clone(arg), env)).collect()
+}
+
+fn env_lookup(key: &str, env: &Environment) -> Option<Rc<Expression>> {
     for map in env.iter().rev() {
-        if let Some(result) = map.get(key.as_str()) {
+        if let Some(result) = map.get(key) {
             return Some(Rc::clone(result));
         }
     }
@@ -112,27 +340,25 @@ fn env_lookup(key: &String, env: &Environment) -> Option<Rc<Expression>> {
 
 //Check for a special form. Returns None if no special form was found, unless an error occurs.
 fn special_form(
-    proc: &String,
+    proc: &str,
     args: &[Rc<Expression>],
     env: &mut Environment,
 ) -> Option<Result<Rc<Expression>, String>> {
-    if !SPECIAL_FORMS.contains(proc.as_str()) {
+    if !SPECIAL_FORMS.contains(proc) {
         return None;
     }
 
-    match proc.as_str() {
-        "+" => Some(add(args, env)),
-        "-" => Some(sub(args, env)),
-        "*" => Some(mul(args, env)),
-        "/" => Some(div(args, env)),
+    match proc {
         "define" => Some(define(args, env)),
+        "define-record-type" => Some(define_record_type(args, env)),
         "cond" => Some(cond(args, env)),
+        "case" => Some(case(args, env)),
+        "when" => Some(when(args, env)),
+        "unless" => Some(unless(args, env)),
+        "quote" => Some(quote(args, env)),
         "if" => Some(s_if(args, env)),
-        "<" => Some(less_than(args, env)),
-        "=" => Some(equal_to(args, env)),
         "and" => Some(and(args, env)),
         "or" => Some(or(args, env)),
-        "remainder" => Some(remainder(args, env)),
         _ => None,
     }
 }
@@ -153,7 +379,7 @@ fn define(args: &[Rc<Expression>], env: &mut Environment) -> Result<Rc<Expressio
                 .unwrap()
                 .insert(id.clone(), Rc::clone(&bind_value));
 
-            return Ok(bind_value);
+            Ok(bind_value)
         }
         Expression::SExpr(sexpr) => {
             //Check for non-identifier arguments
@@ -164,79 +390,183 @@ fn define(args: &[Rc<Expression>], env: &mut Environment) -> Result<Rc<Expressio
                 ));
             }
 
+            let mut ids = sexpr.iter().map(|expr| match expr.as_ref() {
+                Expression::Identifier(id) => id.clone(),
+                _ => unreachable!(),
+            });
+            let name = match ids.next() {
+                Some(name) => name,
+                None => return Err(String::from("Expected procedure name in define")),
+            };
+
             let proc = Rc::new(Expression::Procedure(Procedure::new(
-                sexpr[1..]
-                    .iter()
-                    .map(|expr| {
-                        if let Expression::Identifier(id) = expr.as_ref() {
-                            id.clone()
-                        } else {
-                            unreachable!()
-                        }
-                    })
-                    .collect(),
+                Some(name.clone()),
+                ids.collect(),
                 Rc::clone(&args[1]),
             )));
 
-            if let Expression::Identifier(id) = sexpr[0].as_ref() {
-                env.last_mut().unwrap().insert(id.clone(), Rc::clone(&proc));
-                return Ok(proc);
-            }
-            unreachable!()
-        }
-        _ => {
-            unimplemented!();
+            env.last_mut().unwrap().insert(name, Rc::clone(&proc));
+            Ok(proc)
         }
+        expr => Err(format!(
+            "Expected identifier or list of identifiers in define, found {}",
+            expr
+        )),
     }
 }
 
-// cond looks at a list of pairs - predicates and values. It evaluates each predicate until
-// one returns #t, then returns the corresponding value.
+// cond looks at a list of clauses, each a predicate followed by a body. It evaluates each
+// predicate until one is true, then evaluates the corresponding body. A clause can also be
+// (predicate => procedure) to pass the predicate's value to the procedure, or just
+// (predicate) to return the value. The last clause may be (else body...).
 fn cond(args: &[Rc<Expression>], env: &mut Environment) -> Result<Rc<Expression>, String> {
     if args.is_empty() {
         return Err(String::from("Empty conditional"));
     }
 
-    for expr in args.iter() {
-        match expr.as_ref() {
-            Expression::SExpr(pair) => {
-                if pair.len() != 2 {
-                    return Err(format!(
-                        "Expected pair in cond expression, found {} expressions",
-                        pair.len()
-                    ));
-                }
+    for (i, expr) in args.iter().enumerate() {
+        let clause = match expr.as_ref() {
+            Expression::SExpr(clause) if !clause.is_empty() => clause,
+            _ => {
+                return Err(format!(
+                    "Expected clause in form (predicate body...), found {}",
+                    expr.as_ref()
+                ));
+            }
+        };
 
-                match eval(Rc::clone(&pair[0]), env) {
-                    Ok(expr) => match expr.as_ref() {
-                        Expression::Boolean(b) => {
-                            if *b {
-                                return eval(Rc::clone(&pair[1]), env);
-                            }
-                        }
-                        _ => {
-                            return Err(format!(
-                                "Expected boolean predicate in cond, found {}",
-                                expr.as_ref()
-                            ));
-                        }
-                    },
-                    Err(msg) => {
-                        return Err(msg);
-                    }
-                }
+        if is_else(&clause[0]) {
+            if i != args.len() - 1 {
+                return Err(String::from("else must be the last clause in cond"));
             }
+            return eval_sequence(&clause[1..], env);
+        }
+
+        let pred = eval(Rc::clone(&clause[0]), env)?;
+        if pred.is_true() {
+            return if clause.len() == 1 {
+                Ok(pred)
+            } else {
+                eval_clause_body(&clause[1..], pred, env)
+            };
+        }
+    }
+
+    Ok(Rc::new(Expression::Unspecified))
+}
+
+// case evaluates its key, then finds the first clause whose list of datums contains a value
+// eqv? to it, and evaluates that clause's body. Like cond, it supports else and =>.
+fn case(args: &[Rc<Expression>], env: &mut Environment) -> Result<Rc<Expression>, String> {
+    if args.is_empty() {
+        return Err(String::from("Expected key in case"));
+    }
+
+    let key = eval(Rc::clone(&args[0]), env)?;
+
+    for (i, expr) in args[1..].iter().enumerate() {
+        let clause = match expr.as_ref() {
+            Expression::SExpr(clause) if !clause.is_empty() => clause,
             _ => {
                 return Err(format!(
-                    "Expected pair in form (predicate value), found {}",
+                    "Expected clause in form ((datum...) body...), found {}",
                     expr.as_ref()
                 ));
             }
+        };
+
+        let matched = match clause[0].as_ref() {
+            _ if is_else(&clause[0]) => {
+                if i != args.len() - 2 {
+                    return Err(String::from("else must be the last clause in case"));
+                }
+                true
+            }
+            Expression::SExpr(datums) => datums.iter().any(|datum| key.eqv(datum)),
+            _ => {
+                return Err(format!(
+                    "Expected list of datums in case clause, found {}",
+                    clause[0].as_ref()
+                ));
+            }
+        };
+
+        if matched {
+            return eval_clause_body(&clause[1..], Rc::clone(&key), env);
         }
     }
-    Err(String::from(
-        "Conditional never found a satisfied predicate",
-    ))
+
+    Ok(Rc::new(Expression::Unspecified))
+}
+
+fn is_else(expr: &Expression) -> bool {
+    match expr {
+        Expression::Identifier(id) => id == "else",
+        _ => false,
+    }
+}
+
+//Evaluates the body of a cond or case clause whose test succeeded with the given value
+fn eval_clause_body(
+    body: &[Rc<Expression>],
+    value: Rc<Expression>,
+    env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    match body.first().map(|expr| expr.as_ref()) {
+        Some(Expression::Identifier(id)) if id == "=>" => {
+            if body.len() != 2 {
+                return Err(String::from("Expected exactly one procedure after =>"));
+            }
+            let receiver = eval(Rc::clone(&body[1]), env)?;
+            call(&receiver, vec![value], env)
+        }
+        _ => eval_sequence(body, env),
+    }
+}
+
+//Evaluates each expression in order, returning the value of the last
+fn eval_sequence(body: &[Rc<Expression>], env: &mut Environment) -> Result<Rc<Expression>, String> {
+    let mut result = Rc::new(Expression::Unspecified);
+    for expr in body.iter() {
+        result = eval(Rc::clone(expr), env)?;
+    }
+    Ok(result)
+}
+
+fn when(args: &[Rc<Expression>], env: &mut Environment) -> Result<Rc<Expression>, String> {
+    if args.is_empty() {
+        return Err(String::from("Expected predicate in when"));
+    }
+
+    if eval(Rc::clone(&args[0]), env)?.is_true() {
+        eval_sequence(&args[1..], env)
+    } else {
+        Ok(Rc::new(Expression::Unspecified))
+    }
+}
+
+fn unless(args: &[Rc<Expression>], env: &mut Environment) -> Result<Rc<Expression>, String> {
+    if args.is_empty() {
+        return Err(String::from("Expected predicate in unless"));
+    }
+
+    if eval(Rc::clone(&args[0]), env)?.is_true() {
+        Ok(Rc::new(Expression::Unspecified))
+    } else {
+        eval_sequence(&args[1..], env)
+    }
+}
+
+// Quote returns its argument without evaluating it
+fn quote(args: &[Rc<Expression>], _env: &mut Environment) -> Result<Rc<Expression>, String> {
+    if args.len() != 1 {
+        return Err(format!(
+            "Expected 1 argument to quote, found {}",
+            args.len()
+        ));
+    }
+
+    Ok(Rc::clone(&args[0]))
 }
 
 // If needs to be a special form to allow one of the values to not be evaluated
@@ -249,66 +579,37 @@ fn s_if(args: &[Rc<Expression>], env: &mut Environment) -> Result<Rc<Expression>
         ));
     }
 
-    let pred = eval(Rc::clone(&args[0]), env)?;
-
-    match pred.as_ref() {
-        Expression::Boolean(b) => {
-            if *b {
-                Ok(eval(Rc::clone(&args[1]), env)?)
-            } else {
-                Ok(eval(Rc::clone(&args[2]), env)?)
-            }
-        }
-        _ => Err(format!(
-            "Expected boolean predicate in if expression, found {:?}",
-            pred.as_ref()
-        )),
+    if eval(Rc::clone(&args[0]), env)?.is_true() {
+        eval(Rc::clone(&args[1]), env)
+    } else {
+        eval(Rc::clone(&args[2]), env)
     }
 }
 
-//Boolean operators need to be special forms to allow for short-circuit evaluation
+//Boolean operators need to be special forms to allow for short-circuit evaluation.
+//Both return the last value they evaluated, rather than a boolean.
 fn and(args: &[Rc<Expression>], env: &mut Environment) -> Result<Rc<Expression>, String> {
-    assert_ne!(args.len(), 0);
+    let mut result = Rc::new(Expression::Boolean(true));
 
     for rc in args.iter() {
-        let pred = eval(Rc::clone(rc), env)?;
-        match pred.as_ref() {
-            Expression::Boolean(b) => {
-                if !*b {
-                    return Ok(Rc::new(Expression::Boolean(false)));
-                }
-            }
-            _ => {
-                return Err(format!(
-                    "Expected boolean in \"and\", found {}",
-                    pred.as_ref()
-                ));
-            }
+        result = eval(Rc::clone(rc), env)?;
+        if !result.is_true() {
+            break;
         }
     }
 
-    Ok(Rc::new(Expression::Boolean(true)))
+    Ok(result)
 }
 
 fn or(args: &[Rc<Expression>], env: &mut Environment) -> Result<Rc<Expression>, String> {
-    assert_ne!(args.len(), 0);
+    let mut result = Rc::new(Expression::Boolean(false));
 
     for rc in args.iter() {
-        let pred = eval(Rc::clone(rc), env)?;
-        match pred.as_ref() {
-            Expression::Boolean(b) => {
-                if *b {
-                    return Ok(Rc::new(Expression::Boolean(true)));
-                }
-            }
-            _ => {
-                return Err(format!(
-                    "Expected boolean in \"or\", found {}",
-                    pred.as_ref()
-                ));
-            }
+        result = eval(Rc::clone(rc), env)?;
+        if result.is_true() {
+            break;
         }
     }
 
-    Ok(Rc::new(Expression::Boolean(false)))
+    Ok(result)
 }
@@ -0,0 +1,186 @@
+use crate::types::*;
+use std::rc::Rc;
+
+fn expect_identifier<'a>(expr: &'a Expression, what: &str) -> Result<&'a String, String> {
+    match expr {
+        Expression::Identifier(id) => Ok(id),
+        _ => Err(format!(
+            "Expected identifier for {} in define-record-type, found {}",
+            what, expr
+        )),
+    }
+}
+
+// (define-record-type <name> (constructor field ...) predicate (field accessor [modifier]) ...)
+// binds the type name to its descriptor and defines the procedures for working with it
+pub fn define_record_type(
+    args: &[Rc<Expression>],
+    env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    if args.len() < 3 {
+        return Err(format!(
+            "Expected at least 3 arguments to define-record-type, found {}",
+            args.len()
+        ));
+    }
+
+    let type_id = expect_identifier(&args[0], "type name")?;
+    let predicate_id = expect_identifier(&args[2], "predicate")?;
+
+    //Field specs are either (field accessor) or (field accessor modifier)
+    let mut field_specs = Vec::with_capacity(args.len() - 3);
+    for spec in args[3..].iter() {
+        match spec.as_ref() {
+            Expression::SExpr(spec) if spec.len() == 2 || spec.len() == 3 => {
+                let ids = spec
+                    .iter()
+                    .map(|id| expect_identifier(id, "field spec"))
+                    .collect::<Result<Vec<_>, String>>()?;
+                field_specs.push(ids);
+            }
+            _ => {
+                return Err(format!(
+                    "Expected field spec of the form (field accessor [modifier]), found {}",
+                    spec
+                ));
+            }
+        }
+    }
+
+    //<point> is displayed as point
+    let name = type_id.trim_start_matches('<').trim_end_matches('>');
+    let fields = field_specs.iter().map(|ids| ids[0].clone()).collect();
+    let record_type = Rc::new(RecordType::new(String::from(name), fields));
+
+    let mut bindings = vec![(
+        type_id.clone(),
+        Expression::RecordType(Rc::clone(&record_type)),
+    )];
+
+    let constructor = match args[1].as_ref() {
+        Expression::SExpr(spec) if !spec.is_empty() => spec,
+        expr => {
+            return Err(format!(
+                "Expected constructor of the form (name field ...), found {}",
+                expr
+            ));
+        }
+    };
+    let constructor_id = expect_identifier(&constructor[0], "constructor")?;
+    let mut indices = Vec::with_capacity(constructor.len() - 1);
+    for field in constructor[1..].iter() {
+        let field = expect_identifier(field, "constructor field")?;
+        match record_type.field_index(field) {
+            Some(idx) => indices.push(idx),
+            None => {
+                return Err(format!(
+                    "Constructor field {} is not a field of {}",
+                    field, name
+                ));
+            }
+        }
+    }
+    bindings.push((
+        constructor_id.clone(),
+        Expression::RecordProcedure(RecordProcedure::new(
+            constructor_id.clone(),
+            Rc::clone(&record_type),
+            RecordOp::Construct(indices),
+        )),
+    ));
+
+    bindings.push((
+        predicate_id.clone(),
+        Expression::RecordProcedure(RecordProcedure::new(
+            predicate_id.clone(),
+            Rc::clone(&record_type),
+            RecordOp::Predicate,
+        )),
+    ));
+
+    for (idx, ids) in field_specs.iter().enumerate() {
+        bindings.push((
+            ids[1].clone(),
+            Expression::RecordProcedure(RecordProcedure::new(
+                ids[1].clone(),
+                Rc::clone(&record_type),
+                RecordOp::Access(idx),
+            )),
+        ));
+
+        if let Some(modifier) = ids.get(2) {
+            bindings.push((
+                modifier.to_string(),
+                Expression::RecordProcedure(RecordProcedure::new(
+                    modifier.to_string(),
+                    Rc::clone(&record_type),
+                    RecordOp::Modify(idx),
+                )),
+            ));
+        }
+    }
+
+    let frame = env.last_mut().unwrap();
+    for (id, expr) in bindings.into_iter() {
+        frame.insert(id, Rc::new(expr));
+    }
+
+    Ok(Rc::new(Expression::Unspecified))
+}
+
+fn expect_record<'a>(proc: &RecordProcedure, expr: &'a Expression) -> Result<&'a Record, String> {
+    match expr {
+        Expression::Record(record) if record.is_instance(proc.get_type()) => Ok(record),
+        _ => Err(format!(
+            "Expected {} record argument to {}, got {}",
+            proc.get_type().get_name(),
+            proc.get_name(),
+            expr
+        )),
+    }
+}
+
+pub fn call_record_procedure(
+    proc: &RecordProcedure,
+    args: Vec<Rc<Expression>>,
+) -> Result<Rc<Expression>, String> {
+    let arity = match proc.get_op() {
+        RecordOp::Construct(indices) => indices.len(),
+        RecordOp::Predicate | RecordOp::Access(_) => 1,
+        RecordOp::Modify(_) => 2,
+    };
+
+    if args.len() != arity {
+        return Err(format!(
+            "Expected {} arguments to {}, {} were given",
+            arity,
+            proc.get_name(),
+            args.len()
+        ));
+    }
+
+    match proc.get_op() {
+        RecordOp::Construct(indices) => {
+            //Fields the constructor doesn't initialize are left unspecified
+            let unset = Rc::new(Expression::Unspecified);
+            let mut values = vec![unset; proc.get_type().get_fields().len()];
+            for (idx, arg) in indices.iter().zip(args) {
+                values[*idx] = arg;
+            }
+
+            Ok(Rc::new(Expression::Record(Record::new(
+                Rc::clone(proc.get_type()),
+                values,
+            ))))
+        }
+        RecordOp::Predicate => Ok(Rc::new(Expression::Boolean(match args[0].as_ref() {
+            Expression::Record(record) => record.is_instance(proc.get_type()),
+            _ => false,
+        }))),
+        RecordOp::Access(idx) => Ok(expect_record(proc, &args[0])?.get(*idx)),
+        RecordOp::Modify(idx) => {
+            expect_record(proc, &args[0])?.set(*idx, Rc::clone(&args[1]));
+            Ok(Rc::new(Expression::Unspecified))
+        }
+    }
+}
@@ -0,0 +1,311 @@
+use crate::types::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::eval::call;
+
+fn new_vector(elements: Vec<Rc<Expression>>) -> Rc<Expression> {
+    Rc::new(Expression::Vector(RefCell::new(elements)))
+}
+
+fn new_bytevector(bytes: Vec<u8>) -> Rc<Expression> {
+    Rc::new(Expression::Bytevector(RefCell::new(bytes)))
+}
+
+fn expect_vector<'a>(
+    expr: &'a Expression,
+    name: &str,
+) -> Result<&'a RefCell<Vec<Rc<Expression>>>, String> {
+    match expr {
+        Expression::Vector(v) => Ok(v),
+        _ => Err(format!(
+            "Expected vector argument to {}, got {}",
+            name, expr
+        )),
+    }
+}
+
+fn expect_bytevector<'a>(expr: &'a Expression, name: &str) -> Result<&'a RefCell<Vec<u8>>, String> {
+    match expr {
+        Expression::Bytevector(v) => Ok(v),
+        _ => Err(format!(
+            "Expected bytevector argument to {}, got {}",
+            name, expr
+        )),
+    }
+}
+
+fn expect_byte(expr: &Expression, name: &str) -> Result<u8, String> {
+    match expr {
+        Expression::Numeric(Number::Integer(x)) if *x >= 0 && *x <= 255 => Ok(*x as u8),
+        _ => Err(format!("Expected byte argument to {}, got {}", name, expr)),
+    }
+}
+
+//Checks that an index is an integer in the range [0, len)
+fn expect_index(expr: &Expression, len: usize, name: &str) -> Result<usize, String> {
+    match expr {
+        Expression::Numeric(Number::Integer(k)) if *k >= 0 && (*k as usize) < len => {
+            Ok(*k as usize)
+        }
+        Expression::Numeric(Number::Integer(k)) => Err(format!(
+            "Index {} out of range for {} of length {}",
+            k, name, len
+        )),
+        _ => Err(format!("Expected index argument to {}, got {}", name, expr)),
+    }
+}
+
+fn expect_length(expr: &Expression, name: &str) -> Result<usize, String> {
+    match expr {
+        Expression::Numeric(Number::Integer(k)) if *k >= 0 => Ok(*k as usize),
+        _ => Err(format!(
+            "Expected non-negative length argument to {}, got {}",
+            name, expr
+        )),
+    }
+}
+
+//Reads the optional start and end arguments beginning at args[from], defaulting to the
+//whole sequence
+pub(super) fn expect_range(
+    args: &[Rc<Expression>],
+    from: usize,
+    len: usize,
+    name: &str,
+) -> Result<(usize, usize), String> {
+    let start = match args.get(from) {
+        Some(expr) => expect_index(expr, len + 1, name)?,
+        None => 0,
+    };
+    let end = match args.get(from + 1) {
+        Some(expr) => expect_index(expr, len + 1, name)?,
+        None => len,
+    };
+
+    if start > end {
+        return Err(format!(
+            "Start index {} is past end index {} in {}",
+            start, end, name
+        ));
+    }
+    Ok((start, end))
+}
+
+pub fn is_vector(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    Ok(Rc::new(Expression::Boolean(matches!(
+        args[0].as_ref(),
+        Expression::Vector(_)
+    ))))
+}
+
+pub fn make_vector(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let len = expect_length(&args[0], "make-vector")?;
+    let fill = match args.get(1) {
+        Some(fill) => Rc::clone(fill),
+        None => Rc::new(Expression::Boolean(false)),
+    };
+
+    Ok(new_vector(vec![fill; len]))
+}
+
+pub fn vector(args: &[Rc<Expression>], _env: &mut Environment) -> Result<Rc<Expression>, String> {
+    Ok(new_vector(args.to_vec()))
+}
+
+pub fn vector_length(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let len = expect_vector(&args[0], "vector-length")?.borrow().len();
+    Ok(Rc::new(Expression::from(len as i32)))
+}
+
+pub fn vector_ref(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let v = expect_vector(&args[0], "vector-ref")?.borrow();
+    let k = expect_index(&args[1], v.len(), "vector-ref")?;
+    Ok(Rc::clone(&v[k]))
+}
+
+pub fn vector_set(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    //Arguments are checked before borrowing the vector mutably, since printing an argument
+    //in an error borrows the vector again if it's the vector itself
+    let v = expect_vector(&args[0], "vector-set!")?;
+    let k = expect_index(&args[1], v.borrow().len(), "vector-set!")?;
+    v.borrow_mut()[k] = Rc::clone(&args[2]);
+    Ok(Rc::new(Expression::Unspecified))
+}
+
+pub fn vector_to_list(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let v = expect_vector(&args[0], "vector->list")?.borrow();
+    let (start, end) = expect_range(args, 1, v.len(), "vector->list")?;
+    Ok(Rc::new(Expression::SExpr(v[start..end].to_vec())))
+}
+
+pub fn list_to_vector(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    match args[0].as_ref() {
+        Expression::SExpr(list) => Ok(new_vector(list.clone())),
+        expr => Err(format!(
+            "Expected list argument to list->vector, got {}",
+            expr
+        )),
+    }
+}
+
+pub fn vector_fill(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let v = expect_vector(&args[0], "vector-fill!")?;
+    let (start, end) = expect_range(args, 2, v.borrow().len(), "vector-fill!")?;
+    for slot in v.borrow_mut()[start..end].iter_mut() {
+        *slot = Rc::clone(&args[1]);
+    }
+    Ok(Rc::new(Expression::Unspecified))
+}
+
+pub fn vector_copy(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let v = expect_vector(&args[0], "vector-copy")?.borrow();
+    let (start, end) = expect_range(args, 1, v.len(), "vector-copy")?;
+    Ok(new_vector(v[start..end].to_vec()))
+}
+
+//Collects the i-th element of each vector, for as long as the shortest vector lasts
+fn vector_rows(vectors: &[Rc<Expression>], name: &str) -> Result<Vec<Vec<Rc<Expression>>>, String> {
+    let vectors = vectors
+        .iter()
+        .map(|v| expect_vector(v, name).map(|v| v.borrow().clone()))
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let len = vectors.iter().map(|v| v.len()).min().unwrap_or(0);
+    Ok((0..len)
+        .map(|i| vectors.iter().map(|v| Rc::clone(&v[i])).collect())
+        .collect())
+}
+
+pub fn vector_map(
+    args: &[Rc<Expression>],
+    env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let mut results = Vec::new();
+    for row in vector_rows(&args[1..], "vector-map")? {
+        results.push(call(&args[0], row, env)?);
+    }
+    Ok(new_vector(results))
+}
+
+pub fn vector_for_each(
+    args: &[Rc<Expression>],
+    env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    for row in vector_rows(&args[1..], "vector-for-each")? {
+        call(&args[0], row, env)?;
+    }
+    Ok(Rc::new(Expression::Unspecified))
+}
+
+pub fn is_bytevector(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    Ok(Rc::new(Expression::Boolean(matches!(
+        args[0].as_ref(),
+        Expression::Bytevector(_)
+    ))))
+}
+
+pub fn make_bytevector(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let len = expect_length(&args[0], "make-bytevector")?;
+    let fill = match args.get(1) {
+        Some(fill) => expect_byte(fill, "make-bytevector")?,
+        None => 0,
+    };
+
+    Ok(new_bytevector(vec![fill; len]))
+}
+
+pub fn bytevector(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let bytes = args
+        .iter()
+        .map(|byte| expect_byte(byte, "bytevector"))
+        .collect::<Result<Vec<u8>, String>>()?;
+
+    Ok(new_bytevector(bytes))
+}
+
+pub fn bytevector_length(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let len = expect_bytevector(&args[0], "bytevector-length")?
+        .borrow()
+        .len();
+    Ok(Rc::new(Expression::from(len as i32)))
+}
+
+pub fn bytevector_u8_ref(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let v = expect_bytevector(&args[0], "bytevector-u8-ref")?.borrow();
+    let k = expect_index(&args[1], v.len(), "bytevector-u8-ref")?;
+    Ok(Rc::new(Expression::from(i32::from(v[k]))))
+}
+
+pub fn bytevector_u8_set(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let v = expect_bytevector(&args[0], "bytevector-u8-set!")?;
+    let k = expect_index(&args[1], v.borrow().len(), "bytevector-u8-set!")?;
+    let byte = expect_byte(&args[2], "bytevector-u8-set!")?;
+    v.borrow_mut()[k] = byte;
+    Ok(Rc::new(Expression::Unspecified))
+}
+
+pub fn bytevector_copy(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let v = expect_bytevector(&args[0], "bytevector-copy")?.borrow();
+    let (start, end) = expect_range(args, 1, v.len(), "bytevector-copy")?;
+    Ok(new_bytevector(v[start..end].to_vec()))
+}
+
+pub fn bytevector_append(
+    args: &[Rc<Expression>],
+    _env: &mut Environment,
+) -> Result<Rc<Expression>, String> {
+    let mut bytes = Vec::new();
+    for arg in args.iter() {
+        bytes.extend(expect_bytevector(arg, "bytevector-append")?.borrow().iter());
+    }
+    Ok(new_bytevector(bytes))
+}
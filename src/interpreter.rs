@@ -0,0 +1,132 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::rc::Rc;
+
+use crate::convert::*;
+use crate::eval::*;
+use crate::parse::*;
+use crate::types::*;
+
+// Owns the global environment, with the standard library loaded, and lets the host
+// application evaluate expressions in it and expose its own procedures to scripts.
+pub struct Interpreter {
+    env: Environment,
+}
+
+impl Interpreter {
+    pub fn new() -> Interpreter {
+        Interpreter {
+            env: standard_environment(),
+        }
+    }
+
+    pub fn eval(&mut self, expr: Rc<Expression>) -> Result<Rc<Expression>, String> {
+        eval(expr, &mut self.env)
+    }
+
+    // Evaluates every expression in the source in order, returning the value of the last one
+    pub fn eval_str(&mut self, source: &str) -> Result<Rc<Expression>, String> {
+        let mut result = Rc::new(Expression::Unspecified);
+        for expr in parse_str(source)? {
+            result = self.eval(expr)?;
+        }
+        Ok(result)
+    }
+
+    pub fn load_file<P: AsRef<Path>>(&mut self, path: P) -> Result<Rc<Expression>, String> {
+        let path = path.as_ref();
+        let file = File::open(path)
+            .map_err(|err| format!("Could not read {}: {}", path.display(), err))?;
+
+        //Each datum is evaluated as soon as it's read, so definitions made earlier in the file
+        //take effect even if a later part can't be read
+        let mut reader = Reader::new(BufReader::new(file));
+        let mut result = Rc::new(Expression::Unspecified);
+        while let Some((expr, _)) = reader
+            .read()
+            .map_err(|msg| format!("{}: {}", path.display(), msg))?
+        {
+            result = self.eval(expr)?;
+        }
+        Ok(result)
+    }
+
+    // Looks up a global binding
+    pub fn get(&self, name: &str) -> Option<Rc<Expression>> {
+        self.env[0].get(name).map(Rc::clone)
+    }
+
+    // Binds a value in the global environment, as if by define. Fails if the value can't be
+    // converted, like an integer too wide for a Lisp integer.
+    pub fn define<V: IntoLisp>(&mut self, name: &str, value: V) -> Result<(), String> {
+        self.env[0].insert(String::from(name), value.into_lisp()?);
+        Ok(())
+    }
+
+    // Binds a Rust closure as a procedure in the global environment. The closure receives
+    // the evaluated arguments, after their count has been checked against the arity.
+    pub fn register_fn<F>(&mut self, name: &str, arity: Arity, func: F)
+    where
+        F: Fn(&[Rc<Expression>]) -> Result<Rc<Expression>, String> + 'static,
+    {
+        let builtin = Builtin::from_closure(name, arity, func);
+        self.env[0].insert(String::from(name), Rc::new(Expression::Builtin(builtin)));
+    }
+
+    // Binds a Rust function with typed arguments, such as |a: i32, b: String| ..., converting
+    // each argument with FromLisp and the result with IntoLisp. The function may also return
+    // a Result to signal an error.
+    pub fn register_typed<Args, F>(&mut self, name: &str, func: F)
+    where
+        F: LispFn<Args> + 'static,
+    {
+        let arity = func.arity();
+        let id = String::from(name);
+        self.register_fn(name, arity, move |args| func.call_lisp(&id, args));
+    }
+
+    // Binds the record type of a LispRecord as <name>, along with make-name, name?, and a
+    // name-field accessor and set-name-field! modifier for each field
+    pub fn register_record<T: LispRecord>(&mut self) {
+        let record_type = T::record_type();
+        let name = record_type.get_name();
+        let record_procedure = |id: String, op: RecordOp| {
+            let proc = RecordProcedure::new(id.clone(), Rc::clone(&record_type), op);
+            (id, Expression::RecordProcedure(proc))
+        };
+
+        let fields = record_type.get_fields();
+        let mut bindings = vec![
+            (
+                format!("<{}>", name),
+                Expression::RecordType(Rc::clone(&record_type)),
+            ),
+            record_procedure(
+                format!("make-{}", name),
+                RecordOp::Construct((0..fields.len()).collect()),
+            ),
+            record_procedure(format!("{}?", name), RecordOp::Predicate),
+        ];
+        for (idx, field) in fields.iter().enumerate() {
+            bindings.push(record_procedure(
+                format!("{}-{}", name, field),
+                RecordOp::Access(idx),
+            ));
+            bindings.push(record_procedure(
+                format!("set-{}-{}!", name, field),
+                RecordOp::Modify(idx),
+            ));
+        }
+
+        for (id, expr) in bindings.into_iter() {
+            self.env[0].insert(id, Rc::new(expr));
+        }
+    }
+}
+
+impl Default for Interpreter {
+    fn default() -> Interpreter {
+        Interpreter::new()
+    }
+}
@@ -0,0 +1,290 @@
+use crate::parse::{ErrorKind, ParseError};
+use crate::printer::CHAR_NAMES;
+
+// The pieces data are written with. Whitespace and comments between them are skipped, except
+// for datum comments, which comment out the whole datum after them and so are left to the
+// parser.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token<'a> {
+    //( or [
+    Open(char),
+    //) or ]
+    Close(char),
+    //#(
+    Vector,
+    //#u8(
+    Bytevector,
+    Quote,
+    //#;
+    DatumComment,
+    //#n=
+    LabelDef(usize),
+    //#n#
+    LabelRef(usize),
+    Char(char),
+    String(String),
+    //Numbers, booleans and identifiers, which are told apart by the parser
+    Atom(&'a str),
+}
+
+// Splits input into tokens. Unless eof is set, the input may only be the start of the source,
+// so running into its end partway through a token is reported as an error that is_incomplete,
+// rather than taken as the end of the token.
+pub struct Lexer<'a> {
+    input: &'a str,
+    pos: usize,
+    eof: bool,
+}
+
+fn is_separator(c: char) -> bool {
+    c.is_whitespace() || matches!(c, '(' | ')' | '[' | ']' | '"' | ';')
+}
+
+impl<'a> Lexer<'a> {
+    pub fn new(input: &'a str, eof: bool) -> Lexer<'a> {
+        Lexer { input, pos: 0, eof }
+    }
+
+    // How far into the input has been read, in bytes
+    pub fn offset(&self) -> usize {
+        self.pos
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn error(&self, kind: ErrorKind, offset: usize) -> ParseError {
+        ParseError { kind, offset }
+    }
+
+    // Reads the next token and the offset it starts at, or None at the end of the input
+    pub fn next_token(&mut self) -> Result<Option<(usize, Token<'a>)>, ParseError> {
+        self.skip_atmosphere()?;
+
+        let start = self.pos;
+        let c = match self.rest().chars().next() {
+            Some(c) => c,
+            None => return Ok(None),
+        };
+
+        let token = match c {
+            '(' | '[' => {
+                self.pos += 1;
+                Token::Open(c)
+            }
+            ')' | ']' => {
+                self.pos += 1;
+                Token::Close(c)
+            }
+            '\'' => {
+                self.pos += 1;
+                Token::Quote
+            }
+            '"' => Token::String(self.string()?),
+            '#' => self.hash()?,
+            _ => Token::Atom(self.atom()?),
+        };
+        Ok(Some((start, token)))
+    }
+
+    //Skips whitespace, ; comments to the end of the line, and #| ... |# comments, which nest
+    fn skip_atmosphere(&mut self) -> Result<(), ParseError> {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.pos += rest.len() - trimmed.len();
+
+            if trimmed.starts_with(';') {
+                match trimmed.find('\n') {
+                    Some(end) => self.pos += end,
+                    None if self.eof => self.pos = self.input.len(),
+                    None => return Err(self.error(ErrorKind::UnexpectedEnd, self.pos)),
+                }
+            } else if trimmed.starts_with("#|") {
+                self.block_comment()?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn block_comment(&mut self) -> Result<(), ParseError> {
+        let start = self.pos;
+        let mut depth = 0;
+        loop {
+            let rest = self.rest();
+            if rest.starts_with("#|") {
+                depth += 1;
+                self.pos += 2;
+            } else if rest.starts_with("|#") {
+                depth -= 1;
+                self.pos += 2;
+                if depth == 0 {
+                    return Ok(());
+                }
+            } else {
+                match rest.chars().next() {
+                    Some(c) => self.pos += c.len_utf8(),
+                    None => return Err(self.error(ErrorKind::UnterminatedComment, start)),
+                }
+            }
+        }
+    }
+
+    //Everything up to the next separator
+    fn atom(&mut self) -> Result<&'a str, ParseError> {
+        let rest = self.rest();
+        match rest.find(is_separator) {
+            Some(len) => {
+                self.pos += len;
+                Ok(&rest[..len])
+            }
+            None if self.eof => {
+                self.pos = self.input.len();
+                Ok(rest)
+            }
+            None => Err(self.error(ErrorKind::UnexpectedEnd, self.pos)),
+        }
+    }
+
+    //Syntax starting with #, other than block comments. Anything not recognized here is read
+    //as an atom, such as #t or #x1F.
+    fn hash(&mut self) -> Result<Token<'a>, ParseError> {
+        let start = self.pos;
+        let after = &self.rest()[1..];
+
+        if after.starts_with('(') {
+            self.pos += 2;
+            return Ok(Token::Vector);
+        }
+        if after.starts_with('\\') {
+            return self.char_literal();
+        }
+        if after.starts_with(';') {
+            self.pos += 2;
+            return Ok(Token::DatumComment);
+        }
+        if after.starts_with("u8(") {
+            self.pos += 4;
+            return Ok(Token::Bytevector);
+        }
+
+        let digits = after.len() - after.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        if digits > 0 {
+            let marker = after[digits..].chars().next();
+            if marker == Some('=') || marker == Some('#') {
+                let text = &self.rest()[..digits + 2];
+                let label = after[..digits]
+                    .parse()
+                    .map_err(|_| self.error(ErrorKind::InvalidLiteral(text.to_string()), start))?;
+                self.pos += digits + 2;
+                return Ok(if marker == Some('=') {
+                    Token::LabelDef(label)
+                } else {
+                    Token::LabelRef(label)
+                });
+            }
+        }
+
+        Ok(Token::Atom(self.atom()?))
+    }
+
+    //#\a, #\space or #\x41. The character after #\ is taken even if it's a separator, as in
+    //#\(, so a name is only read if more follows it.
+    fn char_literal(&mut self) -> Result<Token<'a>, ParseError> {
+        let start = self.pos;
+        let body = &self.rest()[2..];
+        let first = match body.chars().next() {
+            Some(c) => c,
+            None if self.eof => {
+                return Err(self.error(ErrorKind::InvalidLiteral(String::from("#\\")), start))
+            }
+            None => return Err(self.error(ErrorKind::UnexpectedEnd, start)),
+        };
+
+        let after = &body[first.len_utf8()..];
+        let len = match after.find(is_separator) {
+            Some(len) => len,
+            None if self.eof => after.len(),
+            None => return Err(self.error(ErrorKind::UnexpectedEnd, start)),
+        };
+        let name = &body[..first.len_utf8() + len];
+        self.pos += 2 + name.len();
+        if len == 0 {
+            return Ok(Token::Char(first));
+        }
+
+        let hex = name
+            .strip_prefix('x')
+            .filter(|hex| hex.chars().all(|c| c.is_ascii_hexdigit()));
+        CHAR_NAMES
+            .iter()
+            .find(|(char_name, _)| *char_name == name)
+            .map(|(_, c)| *c)
+            .or_else(|| {
+                hex.and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .and_then(std::char::from_u32)
+            })
+            .map(Token::Char)
+            .ok_or_else(|| self.error(ErrorKind::InvalidLiteral(format!("#\\{}", name)), start))
+    }
+
+    //String literals, with the escape sequences from R7RS
+    fn string(&mut self) -> Result<String, ParseError> {
+        let start = self.pos;
+        let mut result = String::new();
+        let mut chars = self.rest().char_indices().skip(1);
+
+        let unterminated = self.error(ErrorKind::UnterminatedString, start);
+        while let Some((idx, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.pos += idx + 1;
+                    return Ok(result);
+                }
+                '\\' => match chars.next() {
+                    Some((_, 'a')) => result.push('\u{7}'),
+                    Some((_, 'b')) => result.push('\u{8}'),
+                    Some((_, 't')) => result.push('\t'),
+                    Some((_, 'n')) => result.push('\n'),
+                    Some((_, 'r')) => result.push('\r'),
+                    //\x followed by hex digits and a semicolon
+                    Some((_, 'x')) => {
+                        let mut hex = String::new();
+                        loop {
+                            match chars.next() {
+                                Some((_, ';')) => break,
+                                Some((_, c)) if c.is_ascii_hexdigit() => hex.push(c),
+                                Some(_) => {
+                                    return Err(self.error(
+                                        ErrorKind::InvalidEscape(format!("\\x{}", hex)),
+                                        start + idx,
+                                    ))
+                                }
+                                None => return Err(unterminated),
+                            }
+                        }
+                        match u32::from_str_radix(&hex, 16)
+                            .ok()
+                            .and_then(std::char::from_u32)
+                        {
+                            Some(c) => result.push(c),
+                            None => {
+                                return Err(self.error(
+                                    ErrorKind::InvalidEscape(format!("\\x{};", hex)),
+                                    start + idx,
+                                ))
+                            }
+                        }
+                    }
+                    Some((_, c)) => result.push(c),
+                    None => return Err(unterminated),
+                },
+                _ => result.push(c),
+            }
+        }
+
+        Err(unterminated)
+    }
+}
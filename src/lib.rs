@@ -0,0 +1,20 @@
+// An embeddable Scheme interpreter. Create an Interpreter, expose Rust functions to it with
+// register_fn, and evaluate source with eval_str or load_file.
+mod convert;
+mod eval;
+mod interpreter;
+mod lexer;
+mod parse;
+mod port;
+mod printer;
+mod serialize;
+mod types;
+
+pub use crate::convert::*;
+pub use crate::interpreter::Interpreter;
+pub use crate::parse::{parse_str, Position, Reader};
+pub use crate::port::Port;
+pub use crate::printer::{display, pretty_print, write, PrintOptions};
+pub use crate::serialize::*;
+pub use crate::types::*;
+pub use lisp_derive::{lisp_fn, LispRecord};
@@ -1,55 +1,150 @@
+use std::cell::Cell;
+use std::io::{self, BufRead, Read, Write};
 use std::rc::Rc;
 
 use rustyline::error::ReadlineError;
 use rustyline::Editor;
 
-//Not sure I'm using rust modules correctly
-mod eval;
-mod parse;
-mod types;
+use lisp::{pretty_print, Arity, Expression, Interpreter, Number, PrintOptions, Reader};
 
-use eval::*;
-use parse::*;
+//Feeds the lines typed at the prompt to the REPL's reader. Lines that continue an unfinished
+//datum get their own prompt.
+struct Prompt {
+    editor: Editor<()>,
+    line: Vec<u8>,
+    pos: usize,
+    //Whether nothing but blank lines has been typed since the last datum was read
+    fresh: Rc<Cell<bool>>,
+    exit_code: Rc<Cell<Option<i32>>>,
+}
+
+impl Read for Prompt {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = {
+            let available = self.fill_buf()?;
+            let len = available.len().min(buf.len());
+            buf[..len].copy_from_slice(&available[..len]);
+            len
+        };
+        self.consume(len);
+        Ok(len)
+    }
+}
+
+impl BufRead for Prompt {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.pos == self.line.len() {
+            let prompt = if self.fresh.get() { ">> " } else { ".. " };
+            //Output is line buffered, so anything displayed without a newline would otherwise
+            //show up after the prompt
+            io::stdout().flush()?;
+            match self.editor.readline(prompt) {
+                Ok(line) => {
+                    self.editor.add_history_entry(line.as_str());
+                    if !line.trim().is_empty() {
+                        self.fresh.set(false);
+                    }
+                    //Lines read from a pipe rather than a terminal keep their newline
+                    self.line =
+                        format!("{}\n", line.trim_end_matches(&['\r', '\n'][..])).into_bytes();
+                    self.pos = 0;
+                }
+                Err(ReadlineError::Eof) => return Ok(&[]),
+                //Ending the input lets the REPL exit the same way as for (exit)
+                Err(ReadlineError::Interrupted) => {
+                    println!("Encountered ^C");
+                    self.exit_code.set(Some(0));
+                    return Ok(&[]);
+                }
+                Err(err) => {
+                    println!("Error: {}", err);
+                    self.exit_code.set(Some(0));
+                    return Ok(&[]);
+                }
+            }
+        }
+        Ok(&self.line[self.pos..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos += amt;
+    }
+}
+
+//Exits once the interpreter has been dropped, which flushes the file ports it holds.
+//process::exit skips destructors, so they would otherwise lose buffered output.
+fn exit(interpreter: Interpreter, code: i32) -> ! {
+    drop(interpreter);
+    let _ = io::stdout().flush();
+    std::process::exit(code)
+}
 
 fn main() {
-    let mut ed = Editor::<()>::new();
-    let mut env = load_stdlib();
+    let mut interpreter = Interpreter::new();
+    let options = PrintOptions::default();
+
+    //(exit) fails with an error that unwinds to here, where the exit happens
+    let exit_code = Rc::new(Cell::new(None));
+    let code_cell = Rc::clone(&exit_code);
+    interpreter.register_fn("exit", Arity::Between(0, 1), move |args| {
+        code_cell.set(Some(match args.first().map(|arg| arg.as_ref()) {
+            Some(Expression::Numeric(Number::Integer(code))) => *code,
+            Some(Expression::Boolean(false)) => 1,
+            _ => 0,
+        }));
+        Err(String::from("Exiting"))
+    });
+
+    //Scripts given on the command line are run instead of starting the REPL
+    let scripts: Vec<String> = std::env::args().skip(1).collect();
+    if !scripts.is_empty() {
+        for path in scripts.iter() {
+            if let Err(msg) = interpreter.load_file(path) {
+                if let Some(code) = exit_code.get() {
+                    exit(interpreter, code);
+                }
+                println!("{}", msg);
+                exit(interpreter, 1);
+            }
+        }
+        return;
+    }
+
+    let fresh = Rc::new(Cell::new(true));
+    let mut reader = Reader::new(Prompt {
+        editor: Editor::<()>::new(),
+        line: Vec::new(),
+        pos: 0,
+        fresh: Rc::clone(&fresh),
+        exit_code: Rc::clone(&exit_code),
+    });
 
     loop {
-        match ed.readline(">> ") {
-            Ok(line) => {
-                ed.add_history_entry(line.as_ref());
-
-                match parse_repl_line(line) {
-                    Ok(vec) => {
-                        for expr in vec.into_iter() {
-                            match eval(Rc::clone(&expr), &mut env) {
-                                Ok(result) => {
-                                    println!("{}", result);
-                                }
-                                Err(msg) => {
-                                    println!("{}", msg);
-                                }
-                            }
-                        }
+        fresh.set(true);
+        let read = reader.read();
+        if let Some(code) = exit_code.get() {
+            exit(interpreter, code);
+        }
+        match read {
+            Ok(Some((expr, _))) => match interpreter.eval(expr) {
+                Ok(result) => {
+                    if !matches!(result.as_ref(), Expression::Unspecified) {
+                        println!("{}", pretty_print(&result, &options));
                     }
-                    Err(s) => {
-                        println!("{}", s);
-                        std::process::exit(1);
+                }
+                Err(msg) => {
+                    if let Some(code) = exit_code.get() {
+                        exit(interpreter, code);
                     }
+                    println!("{}", msg);
                 }
-            }
-            Err(ReadlineError::Interrupted) => {
-                println!("Encountered ^C");
-                break;
-            }
-            Err(ReadlineError::Eof) => {
+            },
+            Ok(None) => {
                 println!("Encountered EOF");
                 break;
             }
-            Err(err) => {
-                println!("Error: {}", err);
-                break;
+            Err(msg) => {
+                println!("{}", msg);
             }
         }
     }
@@ -1,116 +1,534 @@
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::fmt;
+use std::io::BufRead;
 use std::rc::Rc;
 
-use nom::{alt, char, delimited, flat_map, named, parse_to, tag, take_till1, ws};
-
-use crate::eval::*;
+use crate::lexer::{Lexer, Token};
 use crate::types::*;
 
-pub fn parse_repl_line(mut line: String) -> Result<Vec<Rc<Expression>>, String> {
-    //Needs to be null-terminated to play well with nom
-    line.push(char::from(0));
+// What went wrong while reading. The incomplete kinds mean the input ended partway through a
+// datum, which only counts as an error at the end of the source.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum ErrorKind {
+    UnexpectedEnd,
+    UnterminatedString,
+    UnterminatedComment,
+    UnterminatedList(char),
+    MissingDatum(String),
+    UnexpectedClose(char),
+    MismatchedClose { expected: char, found: char },
+    InvalidEscape(String),
+    InvalidLiteral(String),
+    NoExactForm(String),
+    InvalidByte(String),
+    UndefinedLabel(usize),
+    DuplicateLabel(usize),
+    CircularLabel(usize),
+    TooDeep,
+}
 
-    let mut slice = line.trim_start();
-    let mut expr_vec = Vec::new();
+impl ErrorKind {
+    pub(crate) fn is_incomplete(&self) -> bool {
+        matches!(
+            self,
+            ErrorKind::UnexpectedEnd
+                | ErrorKind::UnterminatedString
+                | ErrorKind::UnterminatedComment
+                | ErrorKind::UnterminatedList(_)
+                | ErrorKind::MissingDatum(_)
+        )
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ErrorKind::UnexpectedEnd => write!(f, "Unexpected end of input"),
+            ErrorKind::UnterminatedString => write!(f, "Unterminated string"),
+            ErrorKind::UnterminatedComment => write!(f, "Unterminated block comment"),
+            ErrorKind::UnterminatedList(close) => {
+                write!(f, "Unterminated list, missing `{}`", close)
+            }
+            ErrorKind::MissingDatum(after) => write!(f, "Missing datum after `{}`", after),
+            ErrorKind::UnexpectedClose(close) => write!(f, "Unexpected `{}`", close),
+            ErrorKind::MismatchedClose { expected, found } => write!(
+                f,
+                "Expected `{}` to close `{}` but found `{}`",
+                expected,
+                if *expected == ']' { '[' } else { '(' },
+                found
+            ),
+            ErrorKind::InvalidEscape(escape) => {
+                write!(f, "Invalid escape `{}` in string", escape)
+            }
+            ErrorKind::InvalidLiteral(text) => write!(f, "Invalid syntax `{}`", text),
+            ErrorKind::NoExactForm(text) => write!(
+                f,
+                "Cannot read `{}` as exact, since exact rationals are unsupported",
+                text
+            ),
+            ErrorKind::InvalidByte(expr) => write!(f, "Expected byte in bytevector, got {}", expr),
+            ErrorKind::UndefinedLabel(label) => write!(f, "Undefined datum label #{}#", label),
+            ErrorKind::DuplicateLabel(label) => {
+                write!(f, "Datum label #{}= is defined twice", label)
+            }
+            //Only vectors can refer to themselves, since they are the only data the reader
+            //makes that can be changed after they are created
+            ErrorKind::CircularLabel(label) => write!(
+                f,
+                "Datum label #{}# refers to a list that contains it, which can't be circular",
+                label
+            ),
+            ErrorKind::TooDeep => write!(f, "Data nested more than {} deep", MAX_DEPTH),
+        }
+    }
+}
 
-    loop {
-        //The end of the line has been reached
-        if slice.starts_with(char::from(0)) {
-            return Ok(expr_vec);
+// An error along with how far into the input it was found, in bytes
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ParseError {
+    pub kind: ErrorKind,
+    pub offset: usize,
+}
+
+// Where a datum starts in the source. Lines and columns count from 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {} column {}", self.line, self.column)
+    }
+}
+
+// Reads data one at a time from any buffered source, such as a file, standard input or a
+// string. Source is read a line at a time, and only when the data read so far doesn't hold a
+// complete datum. Lines added to a datum that is still open are only scanned for brackets,
+// strings and comments, and the datum is parsed again once they balance. After an error, the
+// rest of the line it was found on is discarded so that reading can carry on.
+pub struct Reader<R: BufRead> {
+    source: R,
+    buffer: String,
+    //How much of the buffer has been read, and the position that corresponds to
+    offset: usize,
+    position: Position,
+    //How much of the buffer past offset has been scanned, and what was left open in it
+    scanned: usize,
+    balance: Balance,
+    eof: bool,
+}
+
+impl<R: BufRead> Reader<R> {
+    pub fn new(source: R) -> Reader<R> {
+        Reader {
+            source,
+            buffer: String::new(),
+            offset: 0,
+            position: Position { line: 1, column: 1 },
+            scanned: 0,
+            balance: Balance::default(),
+            eof: false,
         }
+    }
 
-        match expression(slice) {
-            Ok((remainder, expr)) => {
-                expr_vec.push(Rc::clone(&expr));
-                slice = remainder.trim_start();
+    // Reads the next datum along with where it started, or None once the source is used up
+    pub fn read(&mut self) -> Result<Option<(Rc<Expression>, Position)>, String> {
+        loop {
+            //Right after reading, the rest of the buffer hasn't been scanned and may well
+            //hold a whole datum already
+            let fresh = self.scanned == self.offset;
+            if !(fresh || self.eof || self.balance.is_closed()) {
+                self.fill()?;
+                continue;
             }
-            Err(nom::Err::Incomplete(_)) => {
-                return Err(format!(
-                    "Incomplete input: {}",
-                    slice.trim_end_matches(char::from(0))
-                ));
+
+            let (parsed, end) = {
+                let mut parser = Parser::new(&self.buffer[self.offset..], self.eof);
+                (parser.top_level(), parser.offset())
+            };
+
+            match parsed {
+                Ok(Some((start, expr))) => {
+                    self.advance(start);
+                    let position = self.position;
+                    self.advance(end - start);
+                    return Ok(Some((expr, position)));
+                }
+                Ok(None) => {
+                    self.advance(end);
+                    if self.eof {
+                        return Ok(None);
+                    }
+                    self.fill()?;
+                }
+                Err(err) if err.kind.is_incomplete() && !self.eof => self.fill()?,
+                Err(err) => {
+                    self.advance(err.offset);
+                    let msg = format!("{} at {}", err.kind, self.position);
+                    if err.kind.is_incomplete() {
+                        self.advance(self.buffer.len() - self.offset);
+                    } else {
+                        self.skip_line();
+                    }
+                    return Err(msg);
+                }
             }
-            Err(_) => {
-                return Err(format!(
-                    "Error parsing: {}",
-                    slice.trim_end_matches(char::from(0))
-                ));
+        }
+    }
+
+    // Reads the next character, or None at the end of the source
+    pub fn read_char(&mut self) -> Result<Option<char>, String> {
+        let c = self.peek_char()?;
+        if let Some(c) = c {
+            self.advance(c.len_utf8());
+        }
+        Ok(c)
+    }
+
+    // Returns the next character without reading past it
+    pub fn peek_char(&mut self) -> Result<Option<char>, String> {
+        if self.offset == self.buffer.len() && !self.eof {
+            self.fill()?;
+        }
+        Ok(self.buffer[self.offset..].chars().next())
+    }
+
+    // Reads the rest of the current line, without its line ending, or None at the end of the
+    // source
+    pub fn read_line(&mut self) -> Result<Option<String>, String> {
+        if self.offset == self.buffer.len() && !self.eof {
+            self.fill()?;
+        }
+
+        //The buffer always ends at the end of a line, or of the source
+        let rest = &self.buffer[self.offset..];
+        if rest.is_empty() {
+            return Ok(None);
+        }
+        let len = rest.find('\n').map_or(rest.len(), |idx| idx + 1);
+        let line = String::from(rest[..len].trim_end_matches(&['\n', '\r'][..]));
+        self.advance(len);
+        Ok(Some(line))
+    }
+
+    // Whether there are characters left from what has been read from the source already, so
+    // that reading one wouldn't have to wait on the source
+    pub fn char_ready(&self) -> bool {
+        self.offset < self.buffer.len() || self.eof
+    }
+
+    //Appends the next line of the source to the buffer, dropping what has been read already,
+    //and scans everything after what was scanned before
+    fn fill(&mut self) -> Result<(), String> {
+        self.buffer.drain(..self.offset);
+        self.scanned -= self.offset;
+        self.offset = 0;
+        let read = self
+            .source
+            .read_line(&mut self.buffer)
+            .map_err(|err| format!("Error reading input: {}", err))?;
+        self.eof = read == 0;
+        self.balance.scan(&self.buffer[self.scanned..]);
+        self.scanned = self.buffer.len();
+        Ok(())
+    }
+
+    //Moves past len bytes of the buffer. The scan started at the old offset, so it starts over.
+    fn advance(&mut self, len: usize) {
+        for c in self.buffer[self.offset..self.offset + len].chars() {
+            if c == '\n' {
+                self.position.line += 1;
+                self.position.column = 1;
+            } else {
+                self.position.column += 1;
+            }
+        }
+        self.offset += len;
+        self.scanned = self.offset;
+        self.balance = Balance::default();
+    }
+
+    fn skip_line(&mut self) {
+        let rest = &self.buffer[self.offset..];
+        let len = rest.find('\n').map_or(rest.len(), |idx| idx + 1);
+        self.advance(len);
+    }
+}
+
+// What is left open at the end of some text: brackets, a string or block comments. This only
+// tells when a datum might be complete, so the text can be parsed once rather than after
+// every line. Text is scanned a whole number of lines at a time.
+#[derive(Debug, Default)]
+struct Balance {
+    brackets: usize,
+    comments: usize,
+    string: bool,
+    escape: bool,
+}
+
+impl Balance {
+    fn is_closed(&self) -> bool {
+        self.brackets == 0 && self.comments == 0 && !self.string
+    }
+
+    fn scan(&mut self, text: &str) {
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            if self.string {
+                match c {
+                    _ if self.escape => self.escape = false,
+                    '\\' => self.escape = true,
+                    '"' => self.string = false,
+                    _ => {}
+                }
+            } else if self.comments > 0 {
+                match (c, chars.peek()) {
+                    ('#', Some('|')) => {
+                        chars.next();
+                        self.comments += 1;
+                    }
+                    ('|', Some('#')) => {
+                        chars.next();
+                        self.comments -= 1;
+                    }
+                    _ => {}
+                }
+            } else {
+                match (c, chars.peek()) {
+                    ('(', _) | ('[', _) => self.brackets += 1,
+                    //Too many closers is left to the parser to report
+                    (')', _) | (']', _) => self.brackets = self.brackets.saturating_sub(1),
+                    ('"', _) => self.string = true,
+                    (';', _) => {
+                        chars.by_ref().find(|c| *c == '\n');
+                    }
+                    ('#', Some('|')) => {
+                        chars.next();
+                        self.comments += 1;
+                    }
+                    //The character after #\ is never a delimiter, as in #\(
+                    ('#', Some('\\')) => {
+                        chars.next();
+                        chars.next();
+                    }
+                    _ => {}
+                }
             }
         }
     }
 }
 
-pub fn load_stdlib() -> Environment {
-    let bytes = include_bytes!("stdlib.scm");
-    let mut env = vec![HashMap::new()];
-    let mut buf = String::from_utf8(bytes.to_vec()).unwrap();
-    buf.push(char::from(0));
+//Parses every datum in the input
+pub fn parse_str(input: &str) -> Result<Vec<Rc<Expression>>, String> {
+    let mut reader = Reader::new(input.as_bytes());
+    let mut expr_vec = Vec::new();
+    while let Some((expr, _)) = reader.read()? {
+        expr_vec.push(expr);
+    }
+    Ok(expr_vec)
+}
 
-    let mut slice = buf.trim_start();
+//How deeply data may be nested, which keeps deeply nested input from overflowing the stack
+const MAX_DEPTH: usize = 1000;
 
-    loop {
-        //The end of the line has been reached
-        if slice.starts_with(char::from(0)) {
-            return env;
+// Builds data from tokens by recursive descent. Datum labels are resolved as they are read:
+// #n=datum binds n to the datum, which #n# then refers back to. A label is bound to None
+// while its datum is being read.
+struct Parser<'a> {
+    lexer: Lexer<'a>,
+    labels: HashMap<usize, Option<Rc<Expression>>>,
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str, eof: bool) -> Parser<'a> {
+        Parser {
+            lexer: Lexer::new(input, eof),
+            labels: HashMap::new(),
+            depth: 0,
         }
+    }
 
-        match expression(slice) {
-            Ok((remainder, expr)) => {
-                //Ignore result of evaluation
-                let _ = eval(Rc::clone(&expr), &mut env);
-                slice = remainder.trim_start();
+    fn offset(&self) -> usize {
+        self.lexer.offset()
+    }
+
+    fn error<T>(&self, kind: ErrorKind, offset: usize) -> Result<T, ParseError> {
+        Err(ParseError { kind, offset })
+    }
+
+    //The next token, skipping any datum comments along with the data they comment out
+    fn next(&mut self) -> Result<Option<(usize, Token<'a>)>, ParseError> {
+        loop {
+            match self.lexer.next_token()? {
+                Some((start, Token::DatumComment)) => {
+                    self.nested(start, |parser| parser.datum("#;"))?;
+                }
+                token => return Ok(token),
             }
-            Err(nom::Err::Incomplete(_)) => {
-                panic!(format!(
-                    "Incomplete stdandard library: {}",
-                    slice.trim_end_matches(char::from(0))
-                ));
+        }
+    }
+
+    // Reads the next datum and the offset it starts at, or None if there are only whitespace
+    // and comments left
+    fn top_level(&mut self) -> Result<Option<(usize, Rc<Expression>)>, ParseError> {
+        match self.next()? {
+            Some((start, token)) => Ok(Some((start, self.datum_from(start, token)?))),
+            None => Ok(None),
+        }
+    }
+
+    //Reads the datum that has to come after a prefix such as '
+    fn datum(&mut self, after: &str) -> Result<Rc<Expression>, ParseError> {
+        match self.next()? {
+            Some((start, token)) => self.datum_from(start, token),
+            None => self.error(ErrorKind::MissingDatum(String::from(after)), self.offset()),
+        }
+    }
+
+    //Runs read one level deeper, failing if that is too deep
+    fn nested<T, F>(&mut self, start: usize, read: F) -> Result<T, ParseError>
+    where
+        F: FnOnce(&mut Parser<'a>) -> Result<T, ParseError>,
+    {
+        if self.depth == MAX_DEPTH {
+            return self.error(ErrorKind::TooDeep, start);
+        }
+        self.depth += 1;
+        let result = read(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn datum_from(&mut self, start: usize, token: Token<'a>) -> Result<Rc<Expression>, ParseError> {
+        self.nested(start, |parser| parser.nested_datum(start, token))
+    }
+
+    fn nested_datum(
+        &mut self,
+        start: usize,
+        token: Token<'a>,
+    ) -> Result<Rc<Expression>, ParseError> {
+        match token {
+            Token::Open(open) => {
+                let close = if open == '[' { ']' } else { ')' };
+                Ok(Rc::new(Expression::SExpr(self.list(start, close)?)))
             }
-            Err(_) => {
-                panic!(format!(
-                    "Error parsing standard library: {}",
-                    slice.trim_end_matches(char::from(0))
-                ));
+            Token::Close(close) => self.error(ErrorKind::UnexpectedClose(close), start),
+            Token::Vector => Ok(Rc::new(Expression::Vector(RefCell::new(
+                self.list(start, ')')?,
+            )))),
+            Token::Bytevector => {
+                let bytes = self
+                    .list(start, ')')?
+                    .iter()
+                    .map(|byte| match byte.as_ref() {
+                        Expression::Numeric(Number::Integer(x)) if *x >= 0 && *x <= 255 => {
+                            Ok(*x as u8)
+                        }
+                        _ => self.error(ErrorKind::InvalidByte(byte.to_string()), start),
+                    })
+                    .collect::<Result<Vec<u8>, ParseError>>()?;
+                Ok(Rc::new(Expression::Bytevector(RefCell::new(bytes))))
+            }
+            //'datum is shorthand for (quote datum)
+            Token::Quote => Ok(Rc::new(Expression::SExpr(vec![
+                Rc::new(Expression::Identifier(String::from("quote"))),
+                self.datum("'")?,
+            ]))),
+            Token::DatumComment => {
+                self.datum("#;")?;
+                self.datum("#;")
+            }
+            Token::LabelDef(label) => self.label_def(start, label),
+            Token::LabelRef(label) => match self.labels.get(&label) {
+                Some(Some(target)) => Ok(Rc::clone(target)),
+                Some(None) => self.error(ErrorKind::CircularLabel(label), start),
+                None => self.error(ErrorKind::UndefinedLabel(label), start),
+            },
+            Token::Char(c) => Ok(Rc::new(Expression::Char(c))),
+            Token::String(s) => Ok(Rc::new(Expression::String(s))),
+            Token::Atom(text) => atom(text, start),
+        }
+    }
+
+    //The elements of a list or vector up to its closing delimiter, which has to be the same
+    //kind as the one that opened it
+    fn list(&mut self, start: usize, close: char) -> Result<Vec<Rc<Expression>>, ParseError> {
+        let mut items = Vec::new();
+        loop {
+            match self.next()? {
+                Some((_, Token::Close(found))) if found == close => return Ok(items),
+                Some((at, Token::Close(found))) => {
+                    return self.error(
+                        ErrorKind::MismatchedClose {
+                            expected: close,
+                            found,
+                        },
+                        at,
+                    )
+                }
+                Some((at, token)) => items.push(self.datum_from(at, token)?),
+                None => return self.error(ErrorKind::UnterminatedList(close), start),
             }
         }
     }
+
+    fn label_def(&mut self, start: usize, label: usize) -> Result<Rc<Expression>, ParseError> {
+        if self.labels.contains_key(&label) {
+            return self.error(ErrorKind::DuplicateLabel(label), start);
+        }
+
+        let target = match self.next()? {
+            //The vector is created first, so references to it inside it can be filled in
+            Some((at, Token::Vector)) => {
+                let vector = Rc::new(Expression::Vector(RefCell::new(Vec::new())));
+                self.labels.insert(label, Some(Rc::clone(&vector)));
+                let items = self.list(at, ')')?;
+                if let Expression::Vector(cell) = vector.as_ref() {
+                    *cell.borrow_mut() = items;
+                }
+                vector
+            }
+            Some((at, token)) => {
+                self.labels.insert(label, None);
+                self.datum_from(at, token)?
+            }
+            None => {
+                let after = format!("#{}=", label);
+                return self.error(ErrorKind::MissingDatum(after), self.offset());
+            }
+        };
+
+        self.labels.insert(label, Some(Rc::clone(&target)));
+        Ok(target)
+    }
 }
 
-named!(expression <&str, Rc<Expression>>, alt!(
-    atom => { |a| a } |
-    sexpr => { |e| Rc::new(Expression::SExpr(e)) }
-));
-
-named!(sexpr <&str, Vec<Rc<Expression>>>, delimited!(
-    char!('('),
-    ws!(many1!(expression)),
-    char!(')')
-));
-
-named!(atom <&str, Rc<Expression>>, alt!(
-    integer => { |i| Rc::new(Expression::from(i)) } |
-    float   => { |f| Rc::new(Expression::from(f)) } |
-    boolean => { |b| Rc::new(Expression::Boolean(b)) } |
-    token   => { |tok: &str| Rc::new(Expression::Identifier(String::from(tok))) }
-));
-
-named!(integer <&str, i32>, flat_map!(
-    token,
-    parse_to!(i32)));
-
-named!(float <&str, f32>, flat_map!(
-    token,
-    parse_to!(f32)));
-
-named!(boolean <&str, bool>, alt!(
-    tag!("#t") => { |_| true } |
-    tag!("#f") => { |_| false }
-));
-
-named!(token <&str, &str>, take_till1!(
-    is_seperator));
-
-//Detects seperator characters, including null-terminator.
-fn is_seperator(c: char) -> bool {
-    c.is_whitespace() || c == ')' || c == '(' || c == char::from(0)
+//Numbers, booleans and identifiers. Anything else starting with # is malformed syntax.
+fn atom(text: &str, start: usize) -> Result<Rc<Expression>, ParseError> {
+    match Number::parse(text, 10) {
+        Ok(num) => return Ok(Rc::new(Expression::Numeric(num))),
+        Err(NumberError::NoExactForm) => {
+            return Err(ParseError {
+                kind: ErrorKind::NoExactForm(String::from(text)),
+                offset: start,
+            })
+        }
+        Err(NumberError::Invalid) => {}
+    }
+
+    match text {
+        "#t" | "#true" => Ok(Rc::new(Expression::Boolean(true))),
+        "#f" | "#false" => Ok(Rc::new(Expression::Boolean(false))),
+        _ if text.starts_with('#') => Err(ParseError {
+            kind: ErrorKind::InvalidLiteral(String::from(text)),
+            offset: start,
+        }),
+        _ => Ok(Rc::new(Expression::Identifier(String::from(text)))),
+    }
 }
@@ -0,0 +1,162 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::io::{BufRead, Cursor, Write};
+use std::rc::Rc;
+
+use crate::parse::Reader;
+use crate::types::*;
+
+enum PortState {
+    //Interactive sources, like standard input, may not have a character ready without waiting
+    Input {
+        reader: Reader<Box<dyn BufRead>>,
+        interactive: bool,
+    },
+    StringOutput(String),
+    //Files, which are buffered until they're flushed or closed, and the console
+    Output(Box<dyn Write>),
+    Closed {
+        input: bool,
+    },
+}
+
+// A source of characters and data to read, or a destination for output. Copies of a port
+// refer to the same port.
+#[derive(Clone)]
+pub struct Port(Rc<RefCell<PortState>>);
+
+impl Port {
+    fn new(state: PortState) -> Port {
+        Port(Rc::new(RefCell::new(state)))
+    }
+
+    pub fn input<R: BufRead + 'static>(source: R) -> Port {
+        Port::new(PortState::Input {
+            reader: Reader::new(Box::new(source)),
+            interactive: false,
+        })
+    }
+
+    pub fn interactive_input<R: BufRead + 'static>(source: R) -> Port {
+        Port::new(PortState::Input {
+            reader: Reader::new(Box::new(source)),
+            interactive: true,
+        })
+    }
+
+    pub fn string_input(text: &str) -> Port {
+        Port::input(Cursor::new(text.to_string().into_bytes()))
+    }
+
+    pub fn output<W: Write + 'static>(sink: W) -> Port {
+        Port::new(PortState::Output(Box::new(sink)))
+    }
+
+    // A port that collects what is written to it, to be retrieved with get_output_string
+    pub fn string_output() -> Port {
+        Port::new(PortState::StringOutput(String::new()))
+    }
+
+    pub fn is_input(&self) -> bool {
+        matches!(
+            *self.0.borrow(),
+            PortState::Input { .. } | PortState::Closed { input: true }
+        )
+    }
+
+    pub fn is_output(&self) -> bool {
+        !self.is_input()
+    }
+
+    fn with_reader<T, F>(&self, func: F) -> Result<T, String>
+    where
+        F: FnOnce(&mut Reader<Box<dyn BufRead>>) -> Result<T, String>,
+    {
+        match &mut *self.0.borrow_mut() {
+            PortState::Input { reader, .. } => func(reader),
+            PortState::Closed { .. } => Err(String::from("Cannot read from a closed port")),
+            _ => Err(String::from("Expected input port")),
+        }
+    }
+
+    // Reads the next datum, or None at the end of the input
+    pub fn read(&self) -> Result<Option<Rc<Expression>>, String> {
+        self.with_reader(|reader| Ok(reader.read()?.map(|(expr, _)| expr)))
+    }
+
+    pub fn read_char(&self) -> Result<Option<char>, String> {
+        self.with_reader(|reader| reader.read_char())
+    }
+
+    pub fn peek_char(&self) -> Result<Option<char>, String> {
+        self.with_reader(|reader| reader.peek_char())
+    }
+
+    pub fn read_line(&self) -> Result<Option<String>, String> {
+        self.with_reader(|reader| reader.read_line())
+    }
+
+    // Whether reading a character would return without waiting for more input
+    pub fn char_ready(&self) -> Result<bool, String> {
+        match &*self.0.borrow() {
+            PortState::Input {
+                reader,
+                interactive: true,
+            } => Ok(reader.char_ready()),
+            PortState::Input { .. } => Ok(true),
+            PortState::Closed { .. } => Err(String::from("Cannot read from a closed port")),
+            _ => Err(String::from("Expected input port")),
+        }
+    }
+
+    pub fn write_str(&self, text: &str) -> Result<(), String> {
+        match &mut *self.0.borrow_mut() {
+            PortState::StringOutput(out) => {
+                out.push_str(text);
+                Ok(())
+            }
+            PortState::Output(sink) => sink
+                .write_all(text.as_bytes())
+                .map_err(|err| format!("Could not write output: {}", err)),
+            PortState::Closed { .. } => Err(String::from("Cannot write to a closed port")),
+            PortState::Input { .. } => Err(String::from("Expected output port")),
+        }
+    }
+
+    // Writes out any output that is buffered
+    pub fn flush(&self) -> Result<(), String> {
+        match &mut *self.0.borrow_mut() {
+            PortState::Output(sink) => sink
+                .flush()
+                .map_err(|err| format!("Could not write output: {}", err)),
+            _ => Ok(()),
+        }
+    }
+
+    // Closes the port, so reading from or writing to it is an error. Buffered output is
+    // flushed first. Closing a port again does nothing.
+    pub fn close(&self) -> Result<(), String> {
+        let flushed = self.flush();
+        let input = self.is_input();
+        *self.0.borrow_mut() = PortState::Closed { input };
+        flushed
+    }
+
+    // What has been written to a string output port so far
+    pub fn get_output_string(&self) -> Option<String> {
+        match &*self.0.borrow() {
+            PortState::StringOutput(out) => Some(out.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Debug for Port {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_input() {
+            f.write_str("Port(input)")
+        } else {
+            f.write_str("Port(output)")
+        }
+    }
+}
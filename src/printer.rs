@@ -0,0 +1,431 @@
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use crate::types::*;
+
+// Forms whose first few arguments stay on the line with the head when pretty printing,
+// with the rest of the body indented by two spaces
+const BODY_FORMS: &[(&str, usize)] = &[
+    ("define", 1),
+    ("define-record-type", 2),
+    ("lambda", 1),
+    ("let", 1),
+    ("let*", 1),
+    ("letrec", 1),
+    ("case", 1),
+    ("when", 1),
+    ("unless", 1),
+];
+
+// The names of characters in #\name syntax, shared with the reader
+pub(crate) const CHAR_NAMES: &[(&str, char)] = &[
+    ("alarm", '\u{7}'),
+    ("backspace", '\u{8}'),
+    ("delete", '\u{7f}'),
+    ("escape", '\u{1b}'),
+    ("newline", '\n'),
+    ("null", '\0'),
+    ("return", '\r'),
+    ("space", ' '),
+    ("tab", '\t'),
+];
+
+// How to print values. Lists nested deeper than max_depth are printed as ..., and only the
+// first max_length elements of a list or vector are printed, followed by ...
+#[derive(Debug, Clone, Copy)]
+pub struct PrintOptions {
+    pub write: bool,
+    pub width: usize,
+    pub max_depth: Option<usize>,
+    pub max_length: Option<usize>,
+}
+
+impl Default for PrintOptions {
+    fn default() -> PrintOptions {
+        PrintOptions {
+            write: true,
+            width: 80,
+            max_depth: None,
+            max_length: None,
+        }
+    }
+}
+
+// Prints a value so the reader can read it back, with strings quoted and escaped. Values with
+// no written form, like procedures and hash tables, are printed as #<...>.
+pub fn write(expr: &Expression) -> String {
+    let mut out = String::new();
+    Printer::new(expr, &PrintOptions::default()).print(expr, 0, &mut out);
+    out
+}
+
+// Prints a value for people to read, with strings printed as their raw contents
+pub fn display(expr: &Expression) -> String {
+    let options = PrintOptions {
+        write: false,
+        ..PrintOptions::default()
+    };
+    let mut out = String::new();
+    Printer::new(expr, &options).print(expr, 0, &mut out);
+    out
+}
+
+// Lays a value out over multiple lines so that it fits in the width where possible, with
+// conventional Lisp indentation
+pub fn pretty_print(expr: &Expression, options: &PrintOptions) -> String {
+    let mut printer = Printer::new(expr, options);
+    printer.measure(expr);
+    let mut out = String::new();
+    printer.pretty(expr, 0, &mut out);
+    out
+}
+
+fn write_string(s: &str, out: &mut String) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            '\u{7}' => out.push_str("\\a"),
+            '\u{8}' => out.push_str("\\b"),
+            c if c.is_control() => out.push_str(&format!("\\x{:x};", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+//Characters without a printed form of their own are written by name or code point
+fn write_char(c: char, out: &mut String) {
+    out.push_str("#\\");
+    match CHAR_NAMES.iter().find(|(_, named)| *named == c) {
+        Some((name, _)) => out.push_str(name),
+        None if c.is_control() || c.is_whitespace() => out.push_str(&format!("x{:x}", c as u32)),
+        None => out.push(c),
+    }
+}
+
+fn quoted(expr: &Expression) -> Option<&Rc<Expression>> {
+    match expr {
+        Expression::SExpr(list)
+            if list.len() == 2
+                && matches!(list[0].as_ref(), Expression::Identifier(id) if id == "quote") =>
+        {
+            Some(&list[1])
+        }
+        _ => None,
+    }
+}
+
+//The values directly inside a compound value
+fn children(expr: &Expression) -> Vec<Rc<Expression>> {
+    match expr {
+        Expression::SExpr(list) => list.clone(),
+        Expression::Vector(v) => v.borrow().clone(),
+        Expression::Record(record) => record.values(),
+        _ => Vec::new(),
+    }
+}
+
+//Values are identified by address, which for values in an Rc is the address of the Rc's
+//contents
+fn address(expr: &Expression) -> *const Expression {
+    expr
+}
+
+//Finds the values that contain themselves, which are the ones that need datum labels
+fn find_cycles(
+    expr: &Expression,
+    on_path: &mut HashSet<*const Expression>,
+    done: &mut HashSet<*const Expression>,
+    cyclic: &mut HashSet<*const Expression>,
+) {
+    let addr = address(expr);
+    if on_path.contains(&addr) {
+        cyclic.insert(addr);
+        return;
+    }
+    if done.contains(&addr) {
+        return;
+    }
+
+    on_path.insert(addr);
+    for child in children(expr).iter() {
+        find_cycles(child, on_path, done, cyclic);
+    }
+    on_path.remove(&addr);
+    done.insert(addr);
+}
+
+fn column(out: &str) -> usize {
+    out.chars().rev().take_while(|c| *c != '\n').count()
+}
+
+fn newline(indent: usize, out: &mut String) {
+    out.push('\n');
+    out.push_str(&" ".repeat(indent));
+}
+
+// How wide a value is when printed flat, and how many values print visits to print it,
+// counting itself
+#[derive(Debug, Clone, Copy)]
+struct Extent {
+    width: usize,
+    visits: usize,
+}
+
+// Values that contain themselves are printed with datum labels, as in #0=#(a #0#), so
+// printing always terminates
+struct Printer<'a> {
+    options: &'a PrintOptions,
+    cyclic: HashSet<*const Expression>,
+    labels: HashMap<*const Expression, usize>,
+    //While measuring, the output range of each value visited and the number of visits made
+    //by the time it was printed. Afterwards, the extents of the values in the order they are
+    //visited, and the next one that pretty will come to.
+    spans: Option<Vec<(usize, usize, usize)>>,
+    extents: Vec<Extent>,
+    next: usize,
+}
+
+impl<'a> Printer<'a> {
+    fn new(expr: &Expression, options: &'a PrintOptions) -> Printer<'a> {
+        let mut cyclic = HashSet::new();
+        find_cycles(expr, &mut HashSet::new(), &mut HashSet::new(), &mut cyclic);
+        Printer {
+            options,
+            cyclic,
+            labels: HashMap::new(),
+            spans: None,
+            extents: Vec::new(),
+            next: 0,
+        }
+    }
+
+    //Prints expr flat once, noting the extent of every value in it. Pretty printing visits
+    //values in the same order, with the same labels and elisions, so it can look them up in
+    //turn rather than printing each subtree again to see whether it fits.
+    fn measure(&mut self, expr: &Expression) {
+        let mut flat = String::new();
+        self.spans = Some(Vec::new());
+        self.print(expr, 0, &mut flat);
+        let spans = self.spans.take().unwrap_or_default();
+        self.labels.clear();
+
+        //Widths are in characters, so count the characters before each byte offset
+        let mut chars = vec![0; flat.len() + 1];
+        for (count, (idx, _)) in flat.char_indices().enumerate() {
+            chars[idx] = count;
+        }
+        chars[flat.len()] = flat.chars().count();
+
+        self.extents = spans
+            .iter()
+            .enumerate()
+            .map(|(idx, &(start, end, visits))| Extent {
+                width: chars[end] - chars[start],
+                visits: visits - idx,
+            })
+            .collect();
+        self.next = 0;
+    }
+
+    //Prints #n# for a value that was already labeled and returns true, or prints the #n=
+    //prefix the first time a cyclic value is seen
+    fn label(&mut self, expr: &Expression, out: &mut String) -> bool {
+        let addr = address(expr);
+        if let Some(label) = self.labels.get(&addr) {
+            out.push_str(&format!("#{}#", label));
+            return true;
+        }
+        if self.cyclic.contains(&addr) {
+            let label = self.labels.len();
+            self.labels.insert(addr, label);
+            out.push_str(&format!("#{}=", label));
+        }
+        false
+    }
+
+    //The elements of a list or vector that are printed, and whether any were left out
+    fn visible<'b>(&self, items: &'b [Rc<Expression>]) -> (&'b [Rc<Expression>], bool) {
+        match self.options.max_length {
+            Some(len) if items.len() > len => (&items[..len], true),
+            _ => (items, false),
+        }
+    }
+
+    fn too_deep(&self, depth: usize) -> bool {
+        self.options.max_depth.is_some_and(|max| depth >= max)
+    }
+
+    fn print_seq(&mut self, open: &str, items: &[Rc<Expression>], depth: usize, out: &mut String) {
+        if self.too_deep(depth) {
+            out.push_str("...");
+            return;
+        }
+
+        let (items, elided) = self.visible(items);
+        out.push_str(open);
+        for (i, expr) in items.iter().enumerate() {
+            if i > 0 {
+                out.push(' ');
+            }
+            self.print(expr, depth + 1, out);
+        }
+        if elided {
+            out.push_str(if items.is_empty() { "..." } else { " ..." });
+        }
+        out.push(')');
+    }
+
+    fn print(&mut self, expr: &Expression, depth: usize, out: &mut String) {
+        let visit = self.spans.as_mut().map(|spans| {
+            spans.push((out.len(), out.len(), 0));
+            spans.len() - 1
+        });
+
+        if !self.label(expr, out) {
+            self.print_unlabeled(expr, depth, out);
+        }
+
+        if let (Some(idx), Some(spans)) = (visit, self.spans.as_mut()) {
+            let visits = spans.len();
+            spans[idx].1 = out.len();
+            spans[idx].2 = visits;
+        }
+    }
+
+    fn print_unlabeled(&mut self, expr: &Expression, depth: usize, out: &mut String) {
+        match expr {
+            Expression::Numeric(x) => out.push_str(&x.to_string()),
+            Expression::Identifier(id) => out.push_str(id),
+            Expression::Boolean(true) => out.push_str("#t"),
+            Expression::Boolean(false) => out.push_str("#f"),
+            Expression::Char(c) if self.options.write => write_char(*c, out),
+            Expression::Char(c) => out.push(*c),
+            Expression::String(s) if self.options.write => write_string(s, out),
+            Expression::String(s) => out.push_str(s),
+            //(quote x) is printed as 'x
+            Expression::SExpr(_) if quoted(expr).is_some() => {
+                out.push('\'');
+                self.print(quoted(expr).unwrap(), depth, out);
+            }
+            Expression::SExpr(list) => self.print_seq("(", list, depth, out),
+            Expression::Vector(v) => self.print_seq("#(", &v.borrow(), depth, out),
+            Expression::Bytevector(v) => {
+                out.push_str("#u8(");
+                let bytes = v.borrow().iter().map(u8::to_string).collect::<Vec<_>>();
+                out.push_str(&bytes.join(" "));
+                out.push(')');
+            }
+            Expression::Procedure(p) => {
+                out.push_str("#<procedure ");
+                if let Some(name) = p.get_name() {
+                    out.push_str(name);
+                    out.push(' ');
+                }
+                out.push('(');
+                out.push_str(&p.get_arg_ids().join(" "));
+                out.push_str(")>");
+            }
+            Expression::Builtin(b) => out.push_str(&format!("#<procedure {}>", b.get_name())),
+            Expression::RecordProcedure(p) => {
+                out.push_str(&format!("#<procedure {}>", p.get_name()))
+            }
+            Expression::HashTable(table) => {
+                out.push_str(&format!("#<hash-table {}>", table.borrow().len()))
+            }
+            Expression::RecordType(rtd) => {
+                out.push_str(&format!("#<record-type {}>", rtd.get_name()))
+            }
+            Expression::Record(record) => {
+                out.push_str("#<");
+                out.push_str(record.get_type().get_name());
+                for (field, value) in record.get_type().get_fields().iter().zip(record.values()) {
+                    out.push_str(&format!(" {}: ", field));
+                    self.print(&value, depth + 1, out);
+                }
+                out.push('>');
+            }
+            Expression::Port(port) if port.is_input() => out.push_str("#<input-port>"),
+            Expression::Port(_) => out.push_str("#<output-port>"),
+            Expression::Environment(_) => out.push_str("#<environment>"),
+            Expression::Eof => out.push_str("#<eof>"),
+            Expression::Unspecified => out.push_str("#<unspecified>"),
+        }
+    }
+
+    fn pretty(&mut self, expr: &Expression, depth: usize, out: &mut String) {
+        let extent = self.extents[self.next];
+        let end = self.next + extent.visits;
+        self.next += 1;
+
+        if column(out) + extent.width <= self.options.width {
+            self.print(expr, depth, out);
+        } else if !self.label(expr, out) {
+            match expr {
+                Expression::SExpr(_) if quoted(expr).is_some() => {
+                    out.push('\'');
+                    self.pretty(quoted(expr).unwrap(), depth, out);
+                }
+                Expression::SExpr(list) => self.pretty_seq("(", list, depth, out),
+                Expression::Vector(v) => self.pretty_seq("#(", &v.borrow(), depth, out),
+                _ => self.print_unlabeled(expr, depth, out),
+            }
+        }
+        self.next = end;
+    }
+
+    //Lists headed by a symbol keep their first argument on the same line and align the rest
+    //under it, except for body forms, which indent their bodies by two. Anything else is laid
+    //out one element per line.
+    fn pretty_seq(&mut self, open: &str, items: &[Rc<Expression>], depth: usize, out: &mut String) {
+        if self.too_deep(depth) {
+            out.push_str("...");
+            return;
+        }
+
+        let start = column(out);
+        let (items, elided) = self.visible(items);
+        out.push_str(open);
+
+        let head = match items.first().map(|head| head.as_ref()) {
+            Some(Expression::Identifier(id)) if open == "(" => Some(id.as_str()),
+            _ => None,
+        };
+        let body_form = head.and_then(|id| {
+            BODY_FORMS
+                .iter()
+                .find(|(name, _)| *name == id)
+                .map(|(_, args)| *args)
+        });
+
+        let (inline, indent) = match (head, body_form) {
+            (Some(_), Some(args)) => (1 + args, start + 2),
+            (Some(id), None) if items.len() > 1 => (2, start + open.len() + id.chars().count() + 1),
+            _ => (1, start + open.len()),
+        };
+
+        for (i, expr) in items.iter().enumerate() {
+            if i >= inline {
+                newline(indent, out);
+            } else if i > 0 {
+                out.push(' ');
+            }
+            self.pretty(expr, depth + 1, out);
+        }
+
+        if elided {
+            if items.is_empty() {
+                out.push_str("...");
+            } else {
+                newline(indent, out);
+                out.push_str("...");
+            }
+        }
+        out.push(')');
+    }
+}
@@ -0,0 +1,1009 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::fmt;
+use std::rc::Rc;
+
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess,
+    VariantAccess, Visitor,
+};
+use serde::ser::{self, Serialize};
+use serde::{forward_to_deserialize_any, Deserialize, Deserializer, Serializer};
+
+use crate::parse::*;
+use crate::types::*;
+
+// Symbols are passed through serde as a newtype struct with this name, so formats that don't
+// know about them see a string, while this one keeps them as symbols
+const SYMBOL_TOKEN: &str = "$lisp::Symbol";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SerdeError(String);
+
+impl fmt::Display for SerdeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for SerdeError {}
+
+impl ser::Error for SerdeError {
+    fn custom<T: fmt::Display>(msg: T) -> SerdeError {
+        SerdeError(msg.to_string())
+    }
+}
+
+impl de::Error for SerdeError {
+    fn custom<T: fmt::Display>(msg: T) -> SerdeError {
+        SerdeError(msg.to_string())
+    }
+}
+
+impl From<SerdeError> for String {
+    fn from(err: SerdeError) -> String {
+        err.0
+    }
+}
+
+// Encodes a Rust value as s-expression text. Sequences become lists, maps and structs
+// become lists of (key value) pairs, enum variants become symbols or lists headed by one,
+// None becomes () and Some(x) becomes (x).
+pub fn to_string<T: Serialize + ?Sized>(value: &T) -> Result<String, SerdeError> {
+    let expr = to_expression(value)?;
+    let mut out = String::new();
+    write_datum(&expr, &mut HashSet::new(), &mut out)?;
+    Ok(out)
+}
+
+// Decodes a Rust value from text containing a single datum
+pub fn from_str<T: DeserializeOwned>(text: &str) -> Result<T, SerdeError> {
+    let mut exprs = parse_str(text).map_err(SerdeError)?;
+    if exprs.len() != 1 {
+        return Err(SerdeError(format!(
+            "Expected a single datum, found {}",
+            exprs.len()
+        )));
+    }
+    from_expression(&exprs.remove(0))
+}
+
+// Converts a Rust value into a Lisp value. Maps and structs become equal? hash tables,
+// with struct fields keyed by symbols.
+pub fn to_expression<T: Serialize + ?Sized>(value: &T) -> Result<Rc<Expression>, SerdeError> {
+    value.serialize(ExpressionSerializer)
+}
+
+pub fn from_expression<T: DeserializeOwned>(expr: &Rc<Expression>) -> Result<T, SerdeError> {
+    T::deserialize(ExpressionDeserializer(Rc::clone(expr)))
+}
+
+//Values that contain themselves can't be written as data, so the compound values being
+//converted are tracked in path to catch them. Returns false if expr is already on the path.
+fn enter(expr: &Expression, path: &mut HashSet<*const Expression>) -> bool {
+    let compound = matches!(
+        expr,
+        Expression::Vector(_)
+            | Expression::HashTable(_)
+            | Expression::Record(_)
+            | Expression::SExpr(_)
+    );
+    !compound || path.insert(expr)
+}
+
+fn contains_itself() -> SerdeError {
+    SerdeError(String::from(
+        "Cannot serialize a value that contains itself",
+    ))
+}
+
+//Prints data the way the reader reads it. Hash tables have no syntax of their own, so
+//they are printed as lists of (key value) pairs.
+fn write_datum(
+    expr: &Expression,
+    path: &mut HashSet<*const Expression>,
+    out: &mut String,
+) -> Result<(), SerdeError> {
+    let write_all =
+        |exprs: &[Rc<Expression>], path: &mut HashSet<*const Expression>, out: &mut String| {
+            for (i, expr) in exprs.iter().enumerate() {
+                if i > 0 {
+                    out.push(' ');
+                }
+                write_datum(expr, path, out)?;
+            }
+            Ok(())
+        };
+
+    if !enter(expr, path) {
+        return Err(contains_itself());
+    }
+    match expr {
+        Expression::Numeric(_)
+        | Expression::Identifier(_)
+        | Expression::Boolean(_)
+        | Expression::Char(_)
+        | Expression::String(_)
+        | Expression::Bytevector(_) => out.push_str(&expr.to_string()),
+        Expression::SExpr(list) => {
+            out.push('(');
+            write_all(list, path, out)?;
+            out.push(')');
+        }
+        Expression::Vector(v) => {
+            out.push_str("#(");
+            write_all(&v.borrow(), path, out)?;
+            out.push(')');
+        }
+        Expression::HashTable(table) => {
+            let pairs = table
+                .borrow()
+                .entries()
+                .into_iter()
+                .map(|(k, v)| Rc::new(Expression::SExpr(vec![k, v])))
+                .collect::<Vec<_>>();
+            out.push('(');
+            write_all(&pairs, path, out)?;
+            out.push(')');
+        }
+        _ => {
+            return Err(SerdeError(format!(
+                "Cannot write {} as data",
+                expr.type_name()
+            )))
+        }
+    }
+    path.remove(&(expr as *const Expression));
+    Ok(())
+}
+
+impl Serialize for Expression {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Acyclic {
+            expr: self,
+            path: &RefCell::new(HashSet::new()),
+        }
+        .serialize(serializer)
+    }
+}
+
+//Serializes a value along with the compound values it is inside of, to catch values that
+//contain themselves
+struct Acyclic<'a> {
+    expr: &'a Expression,
+    path: &'a RefCell<HashSet<*const Expression>>,
+}
+
+impl<'a> Acyclic<'a> {
+    fn inside(&self, expr: &'a Expression) -> Acyclic<'a> {
+        Acyclic {
+            expr,
+            path: self.path,
+        }
+    }
+}
+
+impl<'a> Serialize for Acyclic<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if !enter(self.expr, &mut self.path.borrow_mut()) {
+            return Err(ser::Error::custom(contains_itself()));
+        }
+        let result = self.serialize_entered(serializer);
+        self.path
+            .borrow_mut()
+            .remove(&(self.expr as *const Expression));
+        result
+    }
+}
+
+impl<'a> Acyclic<'a> {
+    fn serialize_entered<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::{SerializeMap, SerializeSeq};
+
+        match self.expr {
+            Expression::Numeric(Number::Integer(x)) => serializer.serialize_i32(*x),
+            Expression::Numeric(Number::Float(x)) => serializer.serialize_f32(*x),
+            Expression::Boolean(b) => serializer.serialize_bool(*b),
+            Expression::Char(c) => serializer.serialize_char(*c),
+            Expression::String(s) => serializer.serialize_str(s),
+            Expression::Identifier(id) => serializer.serialize_newtype_struct(SYMBOL_TOKEN, id),
+            Expression::SExpr(list) => {
+                let mut seq = serializer.serialize_seq(Some(list.len()))?;
+                for expr in list.iter() {
+                    seq.serialize_element(&self.inside(expr))?;
+                }
+                seq.end()
+            }
+            Expression::Vector(v) => {
+                let v = v.borrow();
+                let mut seq = serializer.serialize_seq(Some(v.len()))?;
+                for expr in v.iter() {
+                    seq.serialize_element(&self.inside(expr))?;
+                }
+                seq.end()
+            }
+            Expression::Bytevector(v) => serializer.serialize_bytes(&v.borrow()),
+            Expression::HashTable(table) => {
+                let entries = table.borrow().entries();
+                let mut map = serializer.serialize_map(Some(entries.len()))?;
+                for (k, v) in entries.iter() {
+                    map.serialize_entry(&self.inside(k), &self.inside(v))?;
+                }
+                map.end()
+            }
+            //Records are written as a map from field names to values
+            Expression::Record(record) => {
+                let fields = record.get_type().get_fields();
+                let mut map = serializer.serialize_map(Some(fields.len()))?;
+                for (field, value) in fields.iter().zip(record.values()) {
+                    map.serialize_entry(field, &self.inside(&value))?;
+                }
+                map.end()
+            }
+            Expression::Unspecified => serializer.serialize_unit(),
+            expr => Err(ser::Error::custom(format!(
+                "Cannot serialize {}",
+                expr.type_name()
+            ))),
+        }
+    }
+}
+
+struct ExpressionVisitor;
+
+impl<'de> Visitor<'de> for ExpressionVisitor {
+    type Value = Expression;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a number, boolean, string, symbol, list or map")
+    }
+
+    fn visit_bool<E>(self, b: bool) -> Result<Expression, E> {
+        Ok(Expression::Boolean(b))
+    }
+
+    fn visit_i64<E: de::Error>(self, x: i64) -> Result<Expression, E> {
+        i32::try_from(x)
+            .map(Expression::from)
+            .map_err(|_| E::custom(format!("Integer {} is out of range", x)))
+    }
+
+    fn visit_u64<E: de::Error>(self, x: u64) -> Result<Expression, E> {
+        i32::try_from(x)
+            .map(Expression::from)
+            .map_err(|_| E::custom(format!("Integer {} is out of range", x)))
+    }
+
+    fn visit_f64<E: de::Error>(self, x: f64) -> Result<Expression, E> {
+        Number::float_from_f64(x)
+            .map(Expression::from)
+            .ok_or_else(|| E::custom(format!("Real {} needs more than single precision", x)))
+    }
+
+    fn visit_char<E>(self, c: char) -> Result<Expression, E> {
+        Ok(Expression::Char(c))
+    }
+
+    fn visit_str<E>(self, s: &str) -> Result<Expression, E> {
+        Ok(Expression::from(s))
+    }
+
+    fn visit_string<E>(self, s: String) -> Result<Expression, E> {
+        Ok(Expression::String(s))
+    }
+
+    fn visit_bytes<E>(self, bytes: &[u8]) -> Result<Expression, E> {
+        Ok(Expression::Bytevector(RefCell::new(bytes.to_vec())))
+    }
+
+    fn visit_none<E>(self) -> Result<Expression, E> {
+        Ok(Expression::SExpr(Vec::new()))
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Expression, D::Error> {
+        let expr = Expression::deserialize(deserializer)?;
+        Ok(Expression::SExpr(vec![Rc::new(expr)]))
+    }
+
+    fn visit_unit<E>(self) -> Result<Expression, E> {
+        Ok(Expression::SExpr(Vec::new()))
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Expression, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Expression, A::Error> {
+        let mut list = Vec::new();
+        while let Some(expr) = seq.next_element::<Expression>()? {
+            list.push(Rc::new(expr));
+        }
+        Ok(Expression::SExpr(list))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Expression, A::Error> {
+        let mut table = HashTable::new(Equivalence::Equal);
+        while let Some(key) = map.next_key::<Expression>()? {
+            //A symbol from ExpressionDeserializer
+            if let Expression::String(token) = &key {
+                if token == SYMBOL_TOKEN {
+                    return Ok(Expression::Identifier(map.next_value()?));
+                }
+            }
+            let value = map.next_value::<Expression>()?;
+            table.insert(&Rc::new(key), Rc::new(value));
+        }
+        Ok(Expression::HashTable(RefCell::new(table)))
+    }
+}
+
+impl<'de> Deserialize<'de> for Expression {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Expression, D::Error> {
+        deserializer.deserialize_newtype_struct(SYMBOL_TOKEN, ExpressionVisitor)
+    }
+}
+
+struct ExpressionSerializer;
+
+impl Serializer for ExpressionSerializer {
+    type Ok = Rc<Expression>;
+    type Error = SerdeError;
+    type SerializeSeq = SerializeList;
+    type SerializeTuple = SerializeList;
+    type SerializeTupleStruct = SerializeList;
+    type SerializeTupleVariant = SerializeList;
+    type SerializeMap = SerializeTable;
+    type SerializeStruct = SerializeTable;
+    type SerializeStructVariant = SerializeTable;
+
+    fn serialize_bool(self, b: bool) -> Result<Rc<Expression>, SerdeError> {
+        Ok(Rc::new(Expression::Boolean(b)))
+    }
+
+    fn serialize_i8(self, x: i8) -> Result<Rc<Expression>, SerdeError> {
+        self.serialize_i32(i32::from(x))
+    }
+
+    fn serialize_i16(self, x: i16) -> Result<Rc<Expression>, SerdeError> {
+        self.serialize_i32(i32::from(x))
+    }
+
+    fn serialize_i32(self, x: i32) -> Result<Rc<Expression>, SerdeError> {
+        Ok(Rc::new(Expression::from(x)))
+    }
+
+    fn serialize_i64(self, x: i64) -> Result<Rc<Expression>, SerdeError> {
+        match i32::try_from(x) {
+            Ok(x) => self.serialize_i32(x),
+            Err(_) => Err(SerdeError(format!("Integer {} is out of range", x))),
+        }
+    }
+
+    fn serialize_u8(self, x: u8) -> Result<Rc<Expression>, SerdeError> {
+        self.serialize_i32(i32::from(x))
+    }
+
+    fn serialize_u16(self, x: u16) -> Result<Rc<Expression>, SerdeError> {
+        self.serialize_i32(i32::from(x))
+    }
+
+    fn serialize_u32(self, x: u32) -> Result<Rc<Expression>, SerdeError> {
+        self.serialize_u64(u64::from(x))
+    }
+
+    fn serialize_u64(self, x: u64) -> Result<Rc<Expression>, SerdeError> {
+        match i32::try_from(x) {
+            Ok(x) => self.serialize_i32(x),
+            Err(_) => Err(SerdeError(format!("Integer {} is out of range", x))),
+        }
+    }
+
+    fn serialize_f32(self, x: f32) -> Result<Rc<Expression>, SerdeError> {
+        Ok(Rc::new(Expression::from(x)))
+    }
+
+    fn serialize_f64(self, x: f64) -> Result<Rc<Expression>, SerdeError> {
+        match Number::float_from_f64(x) {
+            Some(x) => self.serialize_f32(x),
+            None => Err(SerdeError(format!(
+                "Real {} needs more than single precision",
+                x
+            ))),
+        }
+    }
+
+    fn serialize_char(self, c: char) -> Result<Rc<Expression>, SerdeError> {
+        Ok(Rc::new(Expression::Char(c)))
+    }
+
+    fn serialize_str(self, s: &str) -> Result<Rc<Expression>, SerdeError> {
+        Ok(Rc::new(Expression::from(s)))
+    }
+
+    fn serialize_bytes(self, bytes: &[u8]) -> Result<Rc<Expression>, SerdeError> {
+        Ok(Rc::new(Expression::Bytevector(RefCell::new(
+            bytes.to_vec(),
+        ))))
+    }
+
+    //Some is wrapped in a list so it can't be mistaken for None, whatever the value is
+    fn serialize_none(self) -> Result<Rc<Expression>, SerdeError> {
+        Ok(Rc::new(Expression::SExpr(Vec::new())))
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(
+        self,
+        value: &T,
+    ) -> Result<Rc<Expression>, SerdeError> {
+        Ok(Rc::new(Expression::SExpr(vec![value.serialize(self)?])))
+    }
+
+    fn serialize_unit(self) -> Result<Rc<Expression>, SerdeError> {
+        Ok(Rc::new(Expression::SExpr(Vec::new())))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Rc<Expression>, SerdeError> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<Rc<Expression>, SerdeError> {
+        Ok(Rc::new(Expression::Identifier(String::from(variant))))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Rc<Expression>, SerdeError> {
+        let expr = value.serialize(self)?;
+        match expr.as_ref() {
+            Expression::String(id) if name == SYMBOL_TOKEN => {
+                Ok(Rc::new(Expression::Identifier(id.clone())))
+            }
+            _ => Ok(expr),
+        }
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Rc<Expression>, SerdeError> {
+        Ok(Rc::new(Expression::SExpr(vec![
+            Rc::new(Expression::Identifier(String::from(variant))),
+            value.serialize(self)?,
+        ])))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeList, SerdeError> {
+        Ok(SerializeList {
+            items: Vec::with_capacity(len.unwrap_or(0)),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeList, SerdeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeList, SerdeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeList, SerdeError> {
+        let mut items = Vec::with_capacity(len + 1);
+        items.push(Rc::new(Expression::Identifier(String::from(variant))));
+        Ok(SerializeList { items })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<SerializeTable, SerdeError> {
+        Ok(SerializeTable {
+            variant: None,
+            table: HashTable::new(Equivalence::Equal),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeTable, SerdeError> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<SerializeTable, SerdeError> {
+        Ok(SerializeTable {
+            variant: Some(variant),
+            table: HashTable::new(Equivalence::Equal),
+            key: None,
+        })
+    }
+}
+
+struct SerializeList {
+    items: Vec<Rc<Expression>>,
+}
+
+impl SerializeList {
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.items.push(value.serialize(ExpressionSerializer)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Rc<Expression>, SerdeError> {
+        Ok(Rc::new(Expression::SExpr(self.items)))
+    }
+}
+
+impl ser::SerializeSeq for SerializeList {
+    type Ok = Rc<Expression>;
+    type Error = SerdeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Rc<Expression>, SerdeError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for SerializeList {
+    type Ok = Rc<Expression>;
+    type Error = SerdeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Rc<Expression>, SerdeError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeList {
+    type Ok = Rc<Expression>;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Rc<Expression>, SerdeError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for SerializeList {
+    type Ok = Rc<Expression>;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Rc<Expression>, SerdeError> {
+        self.finish()
+    }
+}
+
+//Struct variants are written as (variant table)
+struct SerializeTable {
+    variant: Option<&'static str>,
+    table: HashTable,
+    key: Option<Rc<Expression>>,
+}
+
+impl SerializeTable {
+    fn insert_field<T: Serialize + ?Sized>(
+        &mut self,
+        field: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        let key = Rc::new(Expression::Identifier(String::from(field)));
+        self.table
+            .insert(&key, value.serialize(ExpressionSerializer)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Rc<Expression>, SerdeError> {
+        let table = Rc::new(Expression::HashTable(RefCell::new(self.table)));
+        match self.variant {
+            Some(variant) => Ok(Rc::new(Expression::SExpr(vec![
+                Rc::new(Expression::Identifier(String::from(variant))),
+                table,
+            ]))),
+            None => Ok(table),
+        }
+    }
+}
+
+impl ser::SerializeMap for SerializeTable {
+    type Ok = Rc<Expression>;
+    type Error = SerdeError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), SerdeError> {
+        self.key = Some(key.serialize(ExpressionSerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        match self.key.take() {
+            Some(key) => {
+                self.table
+                    .insert(&key, value.serialize(ExpressionSerializer)?);
+                Ok(())
+            }
+            None => Err(SerdeError(String::from(
+                "Map value serialized before its key",
+            ))),
+        }
+    }
+
+    fn end(self) -> Result<Rc<Expression>, SerdeError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStruct for SerializeTable {
+    type Ok = Rc<Expression>;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        field: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        self.insert_field(field, value)
+    }
+
+    fn end(self) -> Result<Rc<Expression>, SerdeError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeTable {
+    type Ok = Rc<Expression>;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        field: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        self.insert_field(field, value)
+    }
+
+    fn end(self) -> Result<Rc<Expression>, SerdeError> {
+        self.finish()
+    }
+}
+
+struct ExpressionDeserializer(Rc<Expression>);
+
+impl ExpressionDeserializer {
+    fn type_error(&self, expected: &str) -> SerdeError {
+        SerdeError(crate::convert::type_error(expected, &self.0))
+    }
+
+    //The (key value) pairs of a hash table, record or list of pairs
+    fn entries(&self) -> Option<Vec<(Rc<Expression>, Rc<Expression>)>> {
+        match self.0.as_ref() {
+            Expression::HashTable(table) => Some(table.borrow().entries()),
+            Expression::Record(record) => Some(
+                record
+                    .get_type()
+                    .get_fields()
+                    .iter()
+                    .map(|field| Rc::new(Expression::Identifier(field.clone())))
+                    .zip(record.values())
+                    .collect(),
+            ),
+            Expression::SExpr(list) => list
+                .iter()
+                .map(|pair| match pair.as_ref() {
+                    Expression::SExpr(pair) if pair.len() == 2 => {
+                        Some((Rc::clone(&pair[0]), Rc::clone(&pair[1])))
+                    }
+                    _ => None,
+                })
+                .collect(),
+            _ => None,
+        }
+    }
+}
+
+impl<'de> Deserializer<'de> for ExpressionDeserializer {
+    type Error = SerdeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self.0.as_ref() {
+            Expression::Numeric(Number::Integer(x)) => visitor.visit_i32(*x),
+            Expression::Numeric(Number::Float(x)) => visitor.visit_f32(*x),
+            Expression::Boolean(b) => visitor.visit_bool(*b),
+            Expression::Char(c) => visitor.visit_char(*c),
+            Expression::String(s) => visitor.visit_string(s.clone()),
+            Expression::Identifier(id) => visitor.visit_string(id.clone()),
+            Expression::SExpr(list) => visitor.visit_seq(SeqDeserializer::new(list.clone())),
+            Expression::Vector(v) => visitor.visit_seq(SeqDeserializer::new(v.borrow().clone())),
+            Expression::Bytevector(v) => visitor.visit_byte_buf(v.borrow().clone()),
+            Expression::HashTable(_) | Expression::Record(_) => {
+                visitor.visit_map(MapDeserializer::new(self.entries().unwrap()))
+            }
+            Expression::Unspecified => visitor.visit_unit(),
+            _ => Err(SerdeError(format!(
+                "Cannot deserialize {}",
+                self.0.type_name()
+            ))),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self.0.as_ref() {
+            Expression::SExpr(list) if list.is_empty() => visitor.visit_none(),
+            Expression::SExpr(list) if list.len() == 1 => {
+                visitor.visit_some(ExpressionDeserializer(Rc::clone(&list[0])))
+            }
+            _ => Err(self.type_error("() or a list of one value")),
+        }
+    }
+
+    //Reals widen to the double they print as
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self.0.as_ref() {
+            Expression::Numeric(Number::Float(x)) => visitor.visit_f64(Number::float_to_f64(*x)),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self.0.as_ref() {
+            Expression::SExpr(list) if list.is_empty() => visitor.visit_unit(),
+            Expression::Unspecified => visitor.visit_unit(),
+            _ => Err(self.type_error("empty list")),
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        self.deserialize_unit(visitor)
+    }
+
+    //Expression asks for itself as a newtype struct, so symbols can be told apart from
+    //strings
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        match self.0.as_ref() {
+            Expression::Identifier(id) if name == SYMBOL_TOKEN => {
+                let symbol = Rc::new(Expression::from(id.as_str()));
+                let marker = Rc::new(Expression::from(SYMBOL_TOKEN));
+                visitor.visit_map(MapDeserializer::new(vec![(marker, symbol)]))
+            }
+            _ if name == SYMBOL_TOKEN => self.deserialize_any(visitor),
+            _ => visitor.visit_newtype_struct(self),
+        }
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self.0.as_ref() {
+            Expression::Char(c) => visitor.visit_char(*c),
+            Expression::String(s) if s.chars().count() == 1 => {
+                visitor.visit_char(s.chars().next().unwrap())
+            }
+            _ => Err(self.type_error("char")),
+        }
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self.entries() {
+            Some(entries) => visitor.visit_map(MapDeserializer::new(entries)),
+            None => Err(self.type_error("hash-table")),
+        }
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        match self.0.as_ref() {
+            Expression::Identifier(id) | Expression::String(id) => {
+                visitor.visit_enum(id.clone().into_deserializer())
+            }
+            Expression::SExpr(list) if !list.is_empty() && list[0].is_identifier() => {
+                visitor.visit_enum(EnumDeserializer(list.clone()))
+            }
+            _ => Err(self.type_error("symbol or list starting with a symbol")),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 str string
+        bytes byte_buf seq tuple tuple_struct identifier ignored_any
+    }
+}
+
+struct SeqDeserializer {
+    items: std::vec::IntoIter<Rc<Expression>>,
+}
+
+impl SeqDeserializer {
+    fn new(items: Vec<Rc<Expression>>) -> SeqDeserializer {
+        SeqDeserializer {
+            items: items.into_iter(),
+        }
+    }
+}
+
+impl<'de> SeqAccess<'de> for SeqDeserializer {
+    type Error = SerdeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, SerdeError> {
+        match self.items.next() {
+            Some(expr) => seed.deserialize(ExpressionDeserializer(expr)).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.items.len())
+    }
+}
+
+struct MapDeserializer {
+    entries: std::vec::IntoIter<(Rc<Expression>, Rc<Expression>)>,
+    value: Option<Rc<Expression>>,
+}
+
+impl MapDeserializer {
+    fn new(entries: Vec<(Rc<Expression>, Rc<Expression>)>) -> MapDeserializer {
+        MapDeserializer {
+            entries: entries.into_iter(),
+            value: None,
+        }
+    }
+}
+
+impl<'de> MapAccess<'de> for MapDeserializer {
+    type Error = SerdeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, SerdeError> {
+        match self.entries.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(ExpressionDeserializer(key)).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, SerdeError> {
+        match self.value.take() {
+            Some(value) => seed.deserialize(ExpressionDeserializer(value)),
+            None => Err(SerdeError(String::from(
+                "Map value requested before its key",
+            ))),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
+    }
+}
+
+//A list whose head names the variant and whose tail holds its contents
+struct EnumDeserializer(Vec<Rc<Expression>>);
+
+impl<'de> EnumAccess<'de> for EnumDeserializer {
+    type Error = SerdeError;
+    type Variant = EnumDeserializer;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, EnumDeserializer), SerdeError> {
+        let variant = seed.deserialize(ExpressionDeserializer(Rc::clone(&self.0[0])))?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> VariantAccess<'de> for EnumDeserializer {
+    type Error = SerdeError;
+
+    fn unit_variant(self) -> Result<(), SerdeError> {
+        match self.0.len() {
+            1 => Ok(()),
+            _ => Err(SerdeError(format!(
+                "Expected no values for variant {}",
+                self.0[0]
+            ))),
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, SerdeError> {
+        match self.0.len() {
+            2 => seed.deserialize(ExpressionDeserializer(Rc::clone(&self.0[1]))),
+            _ => Err(SerdeError(format!(
+                "Expected one value for variant {}",
+                self.0[0]
+            ))),
+        }
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        visitor.visit_seq(SeqDeserializer::new(self.0[1..].to_vec()))
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        match self.0.len() {
+            2 => ExpressionDeserializer(Rc::clone(&self.0[1])).deserialize_map(visitor),
+            _ => Err(SerdeError(format!(
+                "Expected a table of fields for variant {}",
+                self.0[0]
+            ))),
+        }
+    }
+}
@@ -1,10 +1,12 @@
-(define else #t)
+;;; Procedures that are simple enough to define in Scheme itself. This is loaded into
+;;; every interpreter before anything else runs.
 
 (define (not x)
   (if x
     #f
     #t))
 
+;; Written in terms of < and = so only those need to be primitives
 (define (> a b)
   (not
     (or
@@ -20,4 +22,4 @@
   (not (odd? x)))
 
 (define (inc x)
-  (+ x 1))
\ No newline at end of file
+  (+ x 1))
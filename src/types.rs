@@ -1,8 +1,15 @@
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
-use std::ops::{Add, Div, Mul, Sub};
+use std::hash::{Hash, Hasher};
+use std::mem;
 use std::rc::Rc;
 
+use indexmap::IndexMap;
+
+use crate::port::Port;
+use crate::printer;
+
 pub type Environment = Vec<HashMap<String, Rc<Expression>>>;
 
 #[derive(Debug, Clone)]
@@ -11,40 +18,149 @@ pub enum Expression {
     Identifier(String),
     SExpr(Vec<Rc<Expression>>),
     Procedure(Procedure),
+    Builtin(Builtin),
     Boolean(bool),
+    Char(char),
+    String(String),
+    Vector(RefCell<Vec<Rc<Expression>>>),
+    Bytevector(RefCell<Vec<u8>>),
+    HashTable(RefCell<HashTable>),
+    RecordType(Rc<RecordType>),
+    Record(Record),
+    RecordProcedure(RecordProcedure),
+    Port(Port),
+    Environment(EnvSpec),
+    //What reading returns at the end of the input
+    Eof,
+    Unspecified,
 }
 
 impl Expression {
-    pub fn is_number(&self) -> bool {
-        match self {
-            Expression::Numeric(_) => true,
-            _ => false,
+    // eq? compares booleans, integers, symbols and the empty list by value, and everything
+    // else by identity
+    pub fn is_eq(a: &Rc<Expression>, b: &Rc<Expression>) -> bool {
+        match (a.as_ref(), b.as_ref()) {
+            (Expression::Numeric(Number::Float(_)), _) => Rc::ptr_eq(a, b),
+            _ => a.eqv(b),
         }
     }
 
-    pub fn is_identifier(&self) -> bool {
-        match self {
-            Expression::Identifier(_) => true,
-            _ => false,
+    // eqv? also compares floats by value
+    pub fn eqv(&self, other: &Expression) -> bool {
+        match (self, other) {
+            (Expression::Numeric(Number::Integer(x)), Expression::Numeric(Number::Integer(y))) => {
+                x == y
+            }
+            (Expression::Numeric(Number::Float(x)), Expression::Numeric(Number::Float(y))) => {
+                x.to_bits() == y.to_bits()
+            }
+            (Expression::Boolean(x), Expression::Boolean(y)) => x == y,
+            (Expression::Char(x), Expression::Char(y)) => x == y,
+            (Expression::Identifier(x), Expression::Identifier(y)) => x == y,
+            (Expression::SExpr(x), Expression::SExpr(y)) => {
+                (x.is_empty() && y.is_empty()) || std::ptr::eq(self, other)
+            }
+            (Expression::Environment(x), Expression::Environment(y)) => x.is_same(y),
+            (Expression::Eof, Expression::Eof) => true,
+            (Expression::Unspecified, Expression::Unspecified) => true,
+            _ => std::ptr::eq(self, other),
+        }
+    }
+
+    // equal? compares strings, lists, vectors and bytevectors by their contents. Values that
+    // contain themselves are equal if comparing them never finds a difference, so comparing
+    // them terminates.
+    pub fn equal(&self, other: &Expression) -> bool {
+        self.equal_in(other, &mut HashSet::new())
+    }
+
+    //Pairs of values already being compared are taken to be equal, since any difference
+    //between them will be found by the comparison that's already underway
+    fn equal_in(
+        &self,
+        other: &Expression,
+        seen: &mut HashSet<(*const Expression, *const Expression)>,
+    ) -> bool {
+        fn all_equal(
+            x: &[Rc<Expression>],
+            y: &[Rc<Expression>],
+            seen: &mut HashSet<(*const Expression, *const Expression)>,
+        ) -> bool {
+            x.len() == y.len() && x.iter().zip(y).all(|(x, y)| x.equal_in(y, seen))
+        }
+
+        let compound = matches!(
+            (self, other),
+            (Expression::SExpr(_), Expression::SExpr(_))
+                | (Expression::Vector(_), Expression::Vector(_))
+        );
+        if compound && (std::ptr::eq(self, other) || !seen.insert((self, other))) {
+            return true;
         }
+
+        match (self, other) {
+            (Expression::String(x), Expression::String(y)) => x == y,
+            (Expression::SExpr(x), Expression::SExpr(y)) => all_equal(x, y, seen),
+            (Expression::Vector(x), Expression::Vector(y)) => {
+                all_equal(&x.borrow(), &y.borrow(), seen)
+            }
+            (Expression::Bytevector(x), Expression::Bytevector(y)) => *x.borrow() == *y.borrow(),
+            _ => self.eqv(other),
+        }
+    }
+
+    // Every value other than #f counts as true
+    pub fn is_true(&self) -> bool {
+        !matches!(self, Expression::Boolean(false))
+    }
+
+    pub fn is_number(&self) -> bool {
+        matches!(self, Expression::Numeric(_))
+    }
+
+    pub fn is_identifier(&self) -> bool {
+        matches!(self, Expression::Identifier(_))
+    }
+
+    // The name of the value's type, as used in error messages. Records are named by
+    // their record type.
+    pub fn type_name(&self) -> String {
+        let name = match self {
+            Expression::Numeric(Number::Integer(_)) => "integer",
+            Expression::Numeric(Number::Float(_)) => "real",
+            Expression::Identifier(_) => "symbol",
+            Expression::SExpr(_) => "list",
+            Expression::Procedure(_) | Expression::Builtin(_) | Expression::RecordProcedure(_) => {
+                "procedure"
+            }
+            Expression::Boolean(_) => "boolean",
+            Expression::Char(_) => "char",
+            Expression::String(_) => "string",
+            Expression::Vector(_) => "vector",
+            Expression::Bytevector(_) => "bytevector",
+            Expression::HashTable(_) => "hash-table",
+            Expression::RecordType(_) => "record-type",
+            Expression::Record(record) => record.get_type().get_name(),
+            Expression::Port(_) => "port",
+            Expression::Environment(_) => "environment",
+            Expression::Eof => "eof-object",
+            Expression::Unspecified => "unspecified",
+        };
+        String::from(name)
     }
 }
 
+// Expressions display the way write prints them
 impl fmt::Display for Expression {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            Expression::Numeric(x) => write!(f, "{}", x),
-            Expression::Identifier(s) => write!(f, "{}", s),
-            Expression::SExpr(_) => write!(f, "S-Expression"),
-            Expression::Procedure(p) => write!(f, "Procedure with {} arguments", p.arity()),
-            Expression::Boolean(b) => {
-                if *b {
-                    write!(f, "#t")
-                } else {
-                    write!(f, "#f")
-                }
-            }
-        }
+        f.write_str(&printer::write(self))
+    }
+}
+
+// Expressions are equal in the sense of equal?
+impl PartialEq for Expression {
+    fn eq(&self, other: &Expression) -> bool {
+        self.equal(other)
     }
 }
 
@@ -60,15 +176,54 @@ impl From<f32> for Expression {
     }
 }
 
+impl From<bool> for Expression {
+    fn from(x: bool) -> Expression {
+        Expression::Boolean(x)
+    }
+}
+
+impl From<char> for Expression {
+    fn from(x: char) -> Expression {
+        Expression::Char(x)
+    }
+}
+
+impl From<String> for Expression {
+    fn from(x: String) -> Expression {
+        Expression::String(x)
+    }
+}
+
+impl From<&str> for Expression {
+    fn from(x: &str) -> Expression {
+        Expression::String(String::from(x))
+    }
+}
+
+impl From<Vec<Rc<Expression>>> for Expression {
+    fn from(x: Vec<Rc<Expression>>) -> Expression {
+        Expression::SExpr(x)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Procedure {
+    name: Option<String>,
     arg_ids: Vec<String>,
     body: Rc<Expression>,
 }
 
 impl Procedure {
-    pub fn new(arg_ids: Vec<String>, body: Rc<Expression>) -> Procedure {
-        Procedure { arg_ids, body }
+    pub fn new(name: Option<String>, arg_ids: Vec<String>, body: Rc<Expression>) -> Procedure {
+        Procedure {
+            name,
+            arg_ids,
+            body,
+        }
+    }
+
+    pub fn get_name(&self) -> Option<&str> {
+        self.name.as_deref()
     }
 
     pub fn arity(&self) -> usize {
@@ -84,6 +239,379 @@ impl Procedure {
     }
 }
 
+// How many arguments a procedure implemented in Rust accepts
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Arity {
+    Exactly(usize),
+    AtLeast(usize),
+    Between(usize, usize),
+}
+
+impl Arity {
+    pub fn check(self, name: &str, given: usize) -> Result<(), String> {
+        match self {
+            Arity::Exactly(n) if given != n => Err(format!(
+                "Expected {} arguments to {}, {} were given",
+                n, name, given
+            )),
+            Arity::AtLeast(min) if given < min => Err(format!(
+                "Expected at least {} arguments to {}, {} were given",
+                min, name, given
+            )),
+            Arity::Between(min, max) if given < min || given > max => Err(format!(
+                "Expected {} to {} arguments to {}, {} were given",
+                min, max, name, given
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+pub type BuiltinFn = fn(&[Rc<Expression>], &mut Environment) -> Result<Rc<Expression>, String>;
+type BuiltinClosure = dyn Fn(&[Rc<Expression>], &mut Environment) -> Result<Rc<Expression>, String>;
+
+// A procedure implemented in Rust, either one of the primitives or a closure registered by
+// the host application. Its arguments are evaluated, and their count checked against its
+// arity, before it is called.
+#[derive(Clone)]
+pub struct Builtin {
+    name: String,
+    arity: Arity,
+    func: Rc<BuiltinClosure>,
+}
+
+impl Builtin {
+    pub fn new(name: &str, arity: Arity, func: BuiltinFn) -> Builtin {
+        Builtin {
+            name: String::from(name),
+            arity,
+            func: Rc::new(func),
+        }
+    }
+
+    pub fn from_closure<F>(name: &str, arity: Arity, func: F) -> Builtin
+    where
+        F: Fn(&[Rc<Expression>]) -> Result<Rc<Expression>, String> + 'static,
+    {
+        Builtin {
+            name: String::from(name),
+            arity,
+            func: Rc::new(move |args, _env| func(args)),
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn call(
+        &self,
+        args: &[Rc<Expression>],
+        env: &mut Environment,
+    ) -> Result<Rc<Expression>, String> {
+        self.arity.check(&self.name, args.len())?;
+        (self.func)(args, env)
+    }
+}
+
+impl fmt::Debug for Builtin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Builtin")
+            .field("name", &self.name)
+            .field("arity", &self.arity)
+            .finish()
+    }
+}
+
+// Describes a record type created by define-record-type
+#[derive(Debug)]
+pub struct RecordType {
+    name: String,
+    fields: Vec<String>,
+}
+
+impl RecordType {
+    pub fn new(name: String, fields: Vec<String>) -> RecordType {
+        RecordType { name, fields }
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_fields(&self) -> &Vec<String> {
+        &self.fields
+    }
+
+    pub fn field_index(&self, field: &str) -> Option<usize> {
+        self.fields.iter().position(|f| f == field)
+    }
+}
+
+// An instance of a record type, with one value per field
+#[derive(Debug, Clone)]
+pub struct Record {
+    record_type: Rc<RecordType>,
+    values: RefCell<Vec<Rc<Expression>>>,
+}
+
+impl Record {
+    pub fn new(record_type: Rc<RecordType>, values: Vec<Rc<Expression>>) -> Record {
+        Record {
+            record_type,
+            values: RefCell::new(values),
+        }
+    }
+
+    pub fn get_type(&self) -> &Rc<RecordType> {
+        &self.record_type
+    }
+
+    pub fn is_instance(&self, record_type: &Rc<RecordType>) -> bool {
+        Rc::ptr_eq(&self.record_type, record_type)
+    }
+
+    pub fn get(&self, field: usize) -> Rc<Expression> {
+        Rc::clone(&self.values.borrow()[field])
+    }
+
+    pub fn set(&self, field: usize, value: Rc<Expression>) {
+        self.values.borrow_mut()[field] = value;
+    }
+
+    pub fn values(&self) -> Vec<Rc<Expression>> {
+        self.values.borrow().clone()
+    }
+}
+
+// The operations define-record-type generates procedures for. The constructor holds the
+// index of the field each of its arguments initializes.
+#[derive(Debug, Clone)]
+pub enum RecordOp {
+    Construct(Vec<usize>),
+    Predicate,
+    Access(usize),
+    Modify(usize),
+}
+
+#[derive(Debug, Clone)]
+pub struct RecordProcedure {
+    name: String,
+    record_type: Rc<RecordType>,
+    op: RecordOp,
+}
+
+impl RecordProcedure {
+    pub fn new(name: String, record_type: Rc<RecordType>, op: RecordOp) -> RecordProcedure {
+        RecordProcedure {
+            name,
+            record_type,
+            op,
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_type(&self) -> &Rc<RecordType> {
+        &self.record_type
+    }
+
+    pub fn get_op(&self) -> &RecordOp {
+        &self.op
+    }
+}
+
+// An environment for eval to evaluate in. The interaction environment is the global
+// environment of the program that evaluates in it, while a standalone environment holds
+// bindings of its own, separate from the program's.
+#[derive(Debug, Clone)]
+pub enum EnvSpec {
+    Interaction,
+    Standalone(Rc<RefCell<Environment>>),
+}
+
+impl EnvSpec {
+    fn is_same(&self, other: &EnvSpec) -> bool {
+        match (self, other) {
+            (EnvSpec::Interaction, EnvSpec::Interaction) => true,
+            (EnvSpec::Standalone(x), EnvSpec::Standalone(y)) => Rc::ptr_eq(x, y),
+            _ => false,
+        }
+    }
+}
+
+fn hash_eqv<H: Hasher>(expr: &Rc<Expression>, state: &mut H) {
+    mem::discriminant(expr.as_ref()).hash(state);
+    match expr.as_ref() {
+        Expression::Numeric(Number::Integer(x)) => x.hash(state),
+        Expression::Numeric(Number::Float(x)) => x.to_bits().hash(state),
+        Expression::Boolean(b) => b.hash(state),
+        Expression::Char(c) => c.hash(state),
+        Expression::Identifier(s) => s.hash(state),
+        Expression::SExpr(list) if list.is_empty() => {}
+        Expression::Environment(EnvSpec::Interaction) => {}
+        Expression::Environment(EnvSpec::Standalone(env)) => (Rc::as_ptr(env) as usize).hash(state),
+        Expression::Eof | Expression::Unspecified => {}
+        _ => (Rc::as_ptr(expr) as usize).hash(state),
+    }
+}
+
+//At most this many values inside a key are hashed under equal?, so keys that contain
+//themselves can be hashed. Values are visited in the same order for any two equal keys, so
+//they still hash the same.
+const HASH_EQUAL_LIMIT: usize = 64;
+
+fn hash_equal<H: Hasher>(expr: &Rc<Expression>, state: &mut H, budget: &mut usize) {
+    if *budget == 0 {
+        return;
+    }
+    *budget -= 1;
+
+    match expr.as_ref() {
+        Expression::String(s) => s.hash(state),
+        Expression::SExpr(list) => {
+            list.len().hash(state);
+            list.iter().for_each(|expr| hash_equal(expr, state, budget));
+        }
+        Expression::Vector(v) => {
+            let v = v.borrow();
+            v.len().hash(state);
+            v.iter().for_each(|expr| hash_equal(expr, state, budget));
+        }
+        Expression::Bytevector(v) => v.borrow().hash(state),
+        _ => hash_eqv(expr, state),
+    }
+}
+
+// The equivalence predicate a hash table compares its keys with
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Equivalence {
+    Eq,
+    Eqv,
+    Equal,
+    String,
+}
+
+impl Equivalence {
+    pub fn from_name(name: &str) -> Option<Equivalence> {
+        match name {
+            "eq?" => Some(Equivalence::Eq),
+            "eqv?" => Some(Equivalence::Eqv),
+            "equal?" => Some(Equivalence::Equal),
+            "string=?" => Some(Equivalence::String),
+            _ => None,
+        }
+    }
+
+    pub fn test(self, a: &Rc<Expression>, b: &Rc<Expression>) -> bool {
+        match self {
+            Equivalence::Eq => Expression::is_eq(a, b),
+            Equivalence::Eqv => a.eqv(b),
+            Equivalence::Equal | Equivalence::String => a.equal(b),
+        }
+    }
+
+    fn hash<H: Hasher>(self, expr: &Rc<Expression>, state: &mut H) {
+        match self {
+            Equivalence::Eq | Equivalence::Eqv => hash_eqv(expr, state),
+            Equivalence::Equal | Equivalence::String => {
+                let mut budget = HASH_EQUAL_LIMIT;
+                hash_equal(expr, state, &mut budget)
+            }
+        }
+    }
+}
+
+// Wraps a key so that it is hashed and compared using the table's equivalence
+#[derive(Debug, Clone)]
+struct HashKey {
+    key: Rc<Expression>,
+    equivalence: Equivalence,
+}
+
+impl PartialEq for HashKey {
+    fn eq(&self, other: &HashKey) -> bool {
+        self.equivalence.test(&self.key, &other.key)
+    }
+}
+
+impl Eq for HashKey {}
+
+impl Hash for HashKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.equivalence.hash(&self.key, state)
+    }
+}
+
+// Hash tables iterate in insertion order, so output doesn't change from run to run
+#[derive(Debug, Clone)]
+pub struct HashTable {
+    equivalence: Equivalence,
+    entries: IndexMap<HashKey, Rc<Expression>>,
+}
+
+impl HashTable {
+    pub fn new(equivalence: Equivalence) -> HashTable {
+        HashTable {
+            equivalence,
+            entries: IndexMap::new(),
+        }
+    }
+
+    pub fn equivalence(&self) -> Equivalence {
+        self.equivalence
+    }
+
+    fn wrap(&self, key: &Rc<Expression>) -> HashKey {
+        HashKey {
+            key: Rc::clone(key),
+            equivalence: self.equivalence,
+        }
+    }
+
+    pub fn get(&self, key: &Rc<Expression>) -> Option<Rc<Expression>> {
+        self.entries.get(&self.wrap(key)).cloned()
+    }
+
+    pub fn insert(&mut self, key: &Rc<Expression>, value: Rc<Expression>) {
+        self.entries.insert(self.wrap(key), value);
+    }
+
+    pub fn remove(&mut self, key: &Rc<Expression>) -> Option<Rc<Expression>> {
+        //Shifting keeps the remaining entries in insertion order
+        self.entries.shift_remove(&self.wrap(key))
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn keys(&self) -> Vec<Rc<Expression>> {
+        self.entries.keys().map(|k| Rc::clone(&k.key)).collect()
+    }
+
+    pub fn values(&self) -> Vec<Rc<Expression>> {
+        self.entries.values().cloned().collect()
+    }
+
+    pub fn entries(&self) -> Vec<(Rc<Expression>, Rc<Expression>)> {
+        self.entries
+            .iter()
+            .map(|(k, v)| (Rc::clone(&k.key), Rc::clone(v)))
+            .collect()
+    }
+}
+
 #[derive(Debug, Copy, Clone)]
 pub enum Number {
     Integer(i32),
@@ -91,6 +619,23 @@ pub enum Number {
 }
 
 impl Number {
+    // Reals are single precision. A double converts to one only if the real prints as that
+    // same double, so 0.1 converts but 0.1000000001 doesn't.
+    pub fn float_from_f64(x: f64) -> Option<f32> {
+        let y = x as f32;
+        if x.is_nan() || Number::float_to_f64(y) == x {
+            Some(y)
+        } else {
+            None
+        }
+    }
+
+    // The double a real prints as, which for 0.1 is 0.1 rather than the 0.10000000149011612
+    // that widening it would give
+    pub fn float_to_f64(x: f32) -> f64 {
+        x.to_string().parse().unwrap_or(f64::NAN)
+    }
+
     pub fn less_than(&self, other: &Number) -> bool {
         match self {
             Number::Integer(x) => match other {
@@ -118,75 +663,194 @@ impl Number {
     }
 }
 
-impl fmt::Display for Number {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            Number::Integer(x) => write!(f, "{}", x),
-            Number::Float(x) => write!(f, "{}", x),
+// Why text couldn't be read as a number
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum NumberError {
+    Invalid,
+    //A decimal with a fractional part marked exact with #e, like #e1.5, which would need an
+    //exact rational
+    NoExactForm,
+}
+
+impl Number {
+    // Parses a number using Scheme syntax: an optional radix (#x, #o, #b, #d) and exactness
+    // (#e, #i) prefix in either order, followed by an integer in that radix or, in decimal,
+    // a float with optional exponent or one of +inf.0, -inf.0, +nan.0 and -nan.0.
+    pub fn parse(text: &str, default_radix: u32) -> Result<Number, NumberError> {
+        Number::parse_prefixed(text, default_radix).ok_or(NumberError::Invalid)?
+    }
+
+    fn parse_prefixed(text: &str, default_radix: u32) -> Option<Result<Number, NumberError>> {
+        let mut radix = None;
+        let mut exact = None;
+        let mut body = text;
+
+        while body.starts_with('#') {
+            match body[1..].chars().next()?.to_ascii_lowercase() {
+                'x' if radix.is_none() => radix = Some(16),
+                'd' if radix.is_none() => radix = Some(10),
+                'o' if radix.is_none() => radix = Some(8),
+                'b' if radix.is_none() => radix = Some(2),
+                'e' if exact.is_none() => exact = Some(true),
+                'i' if exact.is_none() => exact = Some(false),
+                _ => return None,
+            }
+            body = &body[2..];
         }
+
+        let num = match radix.unwrap_or(default_radix) {
+            10 => parse_decimal(body)?,
+            r => Number::Integer(i32::from_str_radix(body, r).ok()?),
+        };
+
+        Some(match (exact, num) {
+            (Some(true), Number::Float(x)) => {
+                //Without rationals, only integral floats have an exact representation
+                if x.is_finite() && x.fract() == 0.0 && x.abs() <= i32::MAX as f32 {
+                    Ok(Number::Integer(x as i32))
+                } else if x.is_finite() && x.fract() != 0.0 {
+                    Err(NumberError::NoExactForm)
+                } else {
+                    Err(NumberError::Invalid)
+                }
+            }
+            (Some(false), Number::Integer(x)) => Ok(Number::Float(x as f32)),
+            (_, num) => Ok(num),
+        })
     }
-}
 
-impl Add for Number {
-    type Output = Number;
-    fn add(self, other: Number) -> Number {
+    // Formats the number in the given radix. Only integers may be written in a radix other
+    // than 10.
+    pub fn to_string_radix(self, radix: u32) -> Option<String> {
         match self {
-            Number::Integer(x) => match other {
-                Number::Integer(y) => Number::Integer(x + y),
-                Number::Float(y) => Number::Float(x as f32 + y),
-            },
-            Number::Float(x) => match other {
-                Number::Integer(y) => Number::Float(x + y as f32),
-                Number::Float(y) => Number::Float(x + y),
-            },
+            Number::Integer(x) => {
+                let mut magnitude = (x as i64).abs();
+                let mut digits = Vec::new();
+                loop {
+                    digits.push(std::char::from_digit(
+                        (magnitude % radix as i64) as u32,
+                        radix,
+                    )?);
+                    magnitude /= radix as i64;
+                    if magnitude == 0 {
+                        break;
+                    }
+                }
+                if x < 0 {
+                    digits.push('-');
+                }
+                Some(digits.iter().rev().collect())
+            }
+            Number::Float(_) if radix == 10 => Some(self.to_string()),
+            Number::Float(_) => None,
         }
     }
 }
 
-impl Sub for Number {
-    type Output = Number;
-    fn sub(self, other: Number) -> Number {
-        match self {
-            Number::Integer(x) => match other {
-                Number::Integer(y) => Number::Integer(x - y),
-                Number::Float(y) => Number::Float(x as f32 - y),
-            },
-            Number::Float(x) => match other {
-                Number::Integer(y) => Number::Float(x - y as f32),
-                Number::Float(y) => Number::Float(x - y),
-            },
+fn parse_decimal(body: &str) -> Option<Number> {
+    match body {
+        "+inf.0" => return Some(Number::Float(f32::INFINITY)),
+        "-inf.0" => return Some(Number::Float(f32::NEG_INFINITY)),
+        "+nan.0" | "-nan.0" => return Some(Number::Float(f32::NAN)),
+        _ => {}
+    }
+
+    if let Ok(x) = body.parse::<i32>() {
+        return Some(Number::Integer(x));
+    }
+
+    //Rust accepts forms like "inf" and "NaN" that Scheme reads as identifiers, so check the
+    //syntax before handing it off: [sign] digits [. digits] [e [sign] digits]
+    let unsigned = body.trim_start_matches(['+', '-']);
+    if body.len() - unsigned.len() > 1 {
+        return None;
+    }
+
+    let (mantissa, exponent) = match unsigned.find(['e', 'E']) {
+        Some(idx) => (&unsigned[..idx], Some(&unsigned[idx + 1..])),
+        None => (unsigned, None),
+    };
+
+    let mantissa_ok = mantissa.chars().any(|c| c.is_ascii_digit())
+        && mantissa.chars().all(|c| c.is_ascii_digit() || c == '.')
+        && mantissa.matches('.').count() <= 1;
+
+    let exponent_ok = match exponent {
+        Some(exp) => {
+            let digits = exp.strip_prefix(['+', '-']).unwrap_or(exp);
+            !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
         }
+        None => true,
+    };
+
+    if mantissa_ok && exponent_ok {
+        body.parse::<f32>().ok().map(Number::Float)
+    } else {
+        None
     }
 }
 
-impl Mul for Number {
-    type Output = Number;
-    fn mul(self, other: Number) -> Number {
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Number::Integer(x) => match other {
-                Number::Integer(y) => Number::Integer(x * y),
-                Number::Float(y) => Number::Float(x as f32 * y),
-            },
-            Number::Float(x) => match other {
-                Number::Integer(y) => Number::Float(x * y as f32),
-                Number::Float(y) => Number::Float(x * y),
-            },
+            Number::Integer(x) => write!(f, "{}", x),
+            Number::Float(x) => {
+                if x.is_nan() {
+                    write!(f, "+nan.0")
+                } else if x.is_infinite() {
+                    write!(f, "{}inf.0", if *x > 0.0 { "+" } else { "-" })
+                } else if *x != 0.0 && (x.abs() >= 1e21 || x.abs() < 1e-7) {
+                    //Very large or small values use exponent notation
+                    write!(f, "{:e}", x)
+                } else {
+                    //Rust prints the shortest representation that round-trips, but leaves
+                    //off the decimal point for integral values
+                    let s = x.to_string();
+                    if s.contains('.') {
+                        write!(f, "{}", s)
+                    } else {
+                        write!(f, "{}.0", s)
+                    }
+                }
+            }
         }
     }
 }
 
-impl Div for Number {
-    type Output = Number;
-    fn div(self, other: Number) -> Number {
-        match self {
-            Number::Integer(x) => match other {
-                Number::Integer(y) => Number::Integer(x / y),
-                Number::Float(y) => Number::Float(x as f32 / y),
-            },
-            Number::Float(x) => match other {
-                Number::Integer(y) => Number::Float(x / y as f32),
-                Number::Float(y) => Number::Float(x / y),
-            },
+//Integer arithmetic is checked, giving None on overflow or division by zero. Reals follow
+//IEEE rules, so dividing one by zero gives an infinity.
+impl Number {
+    fn checked_op(
+        self,
+        other: Number,
+        int_op: fn(i32, i32) -> Option<i32>,
+        float_op: fn(f32, f32) -> f32,
+    ) -> Option<Number> {
+        match (self, other) {
+            (Number::Integer(x), Number::Integer(y)) => int_op(x, y).map(Number::Integer),
+            (Number::Integer(x), Number::Float(y)) => Some(Number::Float(float_op(x as f32, y))),
+            (Number::Float(x), Number::Integer(y)) => Some(Number::Float(float_op(x, y as f32))),
+            (Number::Float(x), Number::Float(y)) => Some(Number::Float(float_op(x, y))),
         }
     }
+
+    pub fn checked_add(self, other: Number) -> Option<Number> {
+        self.checked_op(other, i32::checked_add, |x, y| x + y)
+    }
+
+    pub fn checked_sub(self, other: Number) -> Option<Number> {
+        self.checked_op(other, i32::checked_sub, |x, y| x - y)
+    }
+
+    pub fn checked_mul(self, other: Number) -> Option<Number> {
+        self.checked_op(other, i32::checked_mul, |x, y| x * y)
+    }
+
+    pub fn checked_div(self, other: Number) -> Option<Number> {
+        self.checked_op(other, i32::checked_div, |x, y| x / y)
+    }
+
+    pub fn checked_rem(self, other: Number) -> Option<Number> {
+        self.checked_op(other, i32::checked_rem, |x, y| x % y)
+    }
 }
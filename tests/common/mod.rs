@@ -0,0 +1,41 @@
+// Helpers shared by the integration tests. Not every test uses all of them.
+#![allow(dead_code)]
+
+use std::rc::Rc;
+
+use lisp::{parse_str, write, Expression, Interpreter};
+
+pub fn read(text: &str) -> Rc<Expression> {
+    let mut exprs = parse_str(text).unwrap();
+    assert_eq!(exprs.len(), 1, "{} should hold one datum", text);
+    exprs.remove(0)
+}
+
+// Reads text, writes it back out, and checks that reading that gives the same text again
+pub fn round_trip(text: &str) -> String {
+    let written = write(&read(text));
+    assert_eq!(
+        write(&read(&written)),
+        written,
+        "{} didn't round trip",
+        text
+    );
+    written
+}
+
+// Evaluates source and writes the result
+pub fn eval(interpreter: &mut Interpreter, source: &str) -> String {
+    write(&interpreter.eval_str(source).unwrap())
+}
+
+// Evaluates source that should fail and returns the error
+pub fn eval_err(interpreter: &mut Interpreter, source: &str) -> String {
+    match interpreter.eval_str(source) {
+        Ok(value) => panic!(
+            "{} evaluated to {} instead of failing",
+            source,
+            write(&value)
+        ),
+        Err(err) => err,
+    }
+}
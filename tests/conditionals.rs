@@ -0,0 +1,86 @@
+mod common;
+
+use common::eval;
+use lisp::Interpreter;
+
+#[test]
+fn only_false_is_false() {
+    let mut interpreter = Interpreter::new();
+    assert_eq!(eval(&mut interpreter, "(if '() 'yes 'no)"), "yes");
+    assert_eq!(eval(&mut interpreter, "(if 0 'yes 'no)"), "yes");
+    assert_eq!(eval(&mut interpreter, "(if \"\" 'yes 'no)"), "yes");
+    assert_eq!(eval(&mut interpreter, "(if #f 'yes 'no)"), "no");
+    assert_eq!(
+        eval(&mut interpreter, "(cond (0 'zero) (#t 'other))"),
+        "zero"
+    );
+}
+
+#[test]
+fn and_or_return_the_last_value_evaluated() {
+    let mut interpreter = Interpreter::new();
+    assert_eq!(eval(&mut interpreter, "(and 1 2 3)"), "3");
+    assert_eq!(eval(&mut interpreter, "(and 1 #f 3)"), "#f");
+    assert_eq!(eval(&mut interpreter, "(or #f 2)"), "2");
+    assert_eq!(eval(&mut interpreter, "(or #f #f)"), "#f");
+    assert_eq!(eval(&mut interpreter, "(and)"), "#t");
+    assert_eq!(eval(&mut interpreter, "(or)"), "#f");
+
+    //Evaluation stops at the value that decides the result
+    assert_eq!(eval(&mut interpreter, "(or 1 (car '()))"), "1");
+    assert_eq!(eval(&mut interpreter, "(and #f (car '()))"), "#f");
+}
+
+#[test]
+fn cond_clauses() {
+    let mut interpreter = Interpreter::new();
+    interpreter
+        .eval_str("(define (second l) (car (cdr l)))")
+        .unwrap();
+    assert_eq!(eval(&mut interpreter, "(cond (#f 1) (else 2 3))"), "3");
+    assert_eq!(eval(&mut interpreter, "(cond (#t 1 2 3))"), "3");
+    assert_eq!(eval(&mut interpreter, "(cond (5))"), "5");
+    assert_eq!(
+        eval(&mut interpreter, "(cond ('(1 2) => second) (else 'none))"),
+        "2"
+    );
+    assert_eq!(eval(&mut interpreter, "(cond (#f 1))"), "#<unspecified>");
+}
+
+#[test]
+fn case_clauses() {
+    let mut interpreter = Interpreter::new();
+    interpreter.eval_str("(define (twice x) (* x 2))").unwrap();
+    assert_eq!(
+        eval(
+            &mut interpreter,
+            "(case 3 ((1 2) 'low) ((3 4) 'mid) (else 'high))"
+        ),
+        "mid"
+    );
+    assert_eq!(
+        eval(&mut interpreter, "(case (car '(b)) ((a) 1) ((b c) 2))"),
+        "2"
+    );
+    assert_eq!(
+        eval(&mut interpreter, "(case 9 ((1) 'a) (else => twice))"),
+        "18"
+    );
+    assert_eq!(
+        eval(&mut interpreter, "(case 2 ((2) => twice) (else 'none))"),
+        "4"
+    );
+    assert_eq!(
+        eval(&mut interpreter, "(case 1 ((2) 'x))"),
+        "#<unspecified>"
+    );
+}
+
+#[test]
+fn when_and_unless() {
+    let mut interpreter = Interpreter::new();
+    assert_eq!(eval(&mut interpreter, "(when #t 1 2)"), "2");
+    assert_eq!(eval(&mut interpreter, "(when #f 1)"), "#<unspecified>");
+    assert_eq!(eval(&mut interpreter, "(unless #f 1 2)"), "2");
+    assert_eq!(eval(&mut interpreter, "(unless #t 1)"), "#<unspecified>");
+}
@@ -0,0 +1,87 @@
+mod common;
+
+use std::collections::HashMap;
+
+use common::{eval, eval_err, read};
+use lisp::{write, FromLisp, Interpreter, IntoLisp};
+
+fn into<T: IntoLisp>(x: T) -> String {
+    write(&x.into_lisp().unwrap())
+}
+
+fn from<T: FromLisp>(source: &str) -> Result<T, String> {
+    T::from_lisp(&read(source))
+}
+
+#[test]
+fn conversions_round_trip() {
+    assert_eq!(into(42u8), "42");
+    assert_eq!(into(-2.5f32), "-2.5");
+    assert_eq!(into(0.1f64), "0.1");
+    assert_eq!(into('a'), "#\\a");
+    assert_eq!(into("text"), "\"text\"");
+    assert_eq!(into(vec![1, 2, 3]), "(1 2 3)");
+    assert_eq!(into((1, "two", true)), "(1 \"two\" #t)");
+
+    assert_eq!(from::<i64>("-7"), Ok(-7));
+    assert_eq!(from::<f32>("3"), Ok(3.0));
+    assert_eq!(from::<f64>("0.1"), Ok(0.1));
+    assert_eq!(from::<Vec<u8>>("#(1 2)"), Ok(vec![1, 2]));
+    assert_eq!(
+        from::<(String, char)>("(\"a\" #\\b)"),
+        Ok((String::from("a"), 'b'))
+    );
+
+    let mut map = HashMap::new();
+    map.insert(String::from("k"), 1);
+    let table = map.clone().into_lisp().unwrap();
+    assert_eq!(HashMap::<String, i32>::from_lisp(&table), Ok(map));
+}
+
+#[test]
+fn conversions_are_checked() {
+    assert_eq!(
+        from::<u8>("256"),
+        Err(String::from("Integer 256 is out of range for u8"))
+    );
+    assert!(u64::MAX.into_lisp().is_err());
+    assert!(std::f64::consts::PI.into_lisp().is_err());
+    assert!(from::<String>("sym").is_err());
+    assert!(from::<(i32, i32)>("(1 2 3)").is_err());
+}
+
+#[test]
+fn missing_values_are_false() {
+    assert_eq!(from::<Option<i32>>("#f"), Ok(None));
+    assert_eq!(from::<Option<i32>>("5"), Ok(Some(5)));
+    assert_eq!(into(None::<i32>), "#f");
+    assert_eq!(into(Some(5)), "5");
+    assert_eq!(into(Some(true)), "#t");
+
+    //#f would read back as None, so it can't stand for a present value
+    assert_eq!(from::<Option<bool>>("#f"), Ok(None));
+    assert!(Some(false).into_lisp().is_err());
+    assert!(Some(None::<i32>).into_lisp().is_err());
+}
+
+#[test]
+fn typed_functions_convert_their_arguments() {
+    let mut interpreter = Interpreter::new();
+    interpreter.register_typed("repeat", |s: String, n: usize| s.repeat(n));
+    interpreter.register_typed("halve", |n: i32| {
+        if n % 2 == 0 {
+            Ok(n / 2)
+        } else {
+            Err(format!("{} is odd", n))
+        }
+    });
+
+    assert_eq!(eval(&mut interpreter, "(repeat \"ab\" 3)"), "\"ababab\"");
+    assert_eq!(
+        eval_err(&mut interpreter, "(repeat 'ab 3)"),
+        "Expected string, got symbol in argument 1 to repeat"
+    );
+    eval_err(&mut interpreter, "(repeat \"ab\")");
+    assert_eq!(eval(&mut interpreter, "(halve 8)"), "4");
+    assert_eq!(eval_err(&mut interpreter, "(halve 3)"), "3 is odd");
+}
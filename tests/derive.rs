@@ -0,0 +1,160 @@
+mod common;
+
+use std::rc::Rc;
+
+use common::eval;
+use lisp::{lisp_fn, write, Expression, FromLisp, Interpreter, IntoLisp, LispRecord};
+
+#[derive(Debug, Clone, PartialEq, LispRecord)]
+struct MyPoint {
+    x: i32,
+    y: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, LispRecord)]
+struct HTTPServer {
+    host_name: String,
+    r#type: char,
+    port: Option<i32>,
+}
+
+#[derive(Debug, Clone, PartialEq, LispRecord)]
+struct ParseJSONValue {
+    depth: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, LispRecord)]
+struct Line {
+    from: MyPoint,
+    to: MyPoint,
+}
+
+#[lisp_fn]
+fn add_points(a: MyPoint, b: MyPoint) -> MyPoint {
+    MyPoint {
+        x: a.x + b.x,
+        y: a.y + b.y,
+    }
+}
+
+#[lisp_fn(name = "checked-port")]
+fn port_or_error(server: HTTPServer) -> Result<i32, String> {
+    server.port.ok_or_else(|| String::from("No port"))
+}
+
+#[test]
+fn record_types_are_named_in_kebab_case() {
+    assert_eq!(MyPoint::record_type().get_name(), "my-point");
+    assert_eq!(HTTPServer::record_type().get_name(), "http-server");
+    assert_eq!(ParseJSONValue::record_type().get_name(), "parse-json-value");
+    assert_eq!(
+        HTTPServer::record_type().get_fields(),
+        &[
+            String::from("host-name"),
+            String::from("type"),
+            String::from("port")
+        ]
+    );
+}
+
+#[test]
+fn record_type_is_shared() {
+    assert!(Rc::ptr_eq(&MyPoint::record_type(), &MyPoint::record_type()));
+}
+
+#[test]
+fn records_round_trip() {
+    let server = HTTPServer {
+        host_name: String::from("localhost"),
+        r#type: 'h',
+        port: Some(8080),
+    };
+    let expr = server.clone().into_lisp().unwrap();
+    assert_eq!(
+        write(&expr),
+        "#<http-server host-name: \"localhost\" type: #\\h port: 8080>"
+    );
+    assert_eq!(HTTPServer::from_lisp(&expr).unwrap(), server);
+
+    let line = Line {
+        from: MyPoint { x: 0, y: 1 },
+        to: MyPoint { x: 2, y: 3 },
+    };
+    assert_eq!(
+        Line::from_lisp(&line.clone().into_lisp().unwrap()).unwrap(),
+        line
+    );
+}
+
+#[test]
+fn from_lisp_checks_the_record_type() {
+    let point = MyPoint { x: 1, y: 2 }.into_lisp().unwrap();
+    assert!(ParseJSONValue::from_lisp(&point).is_err());
+    assert!(MyPoint::from_lisp(&Rc::new(Expression::Boolean(true))).is_err());
+}
+
+#[test]
+fn registered_records_are_usable_from_lisp() {
+    let mut interpreter = Interpreter::new();
+    interpreter.register_record::<MyPoint>();
+    interpreter.register_record::<HTTPServer>();
+
+    interpreter
+        .eval_str("(define p (make-my-point 3 4))")
+        .unwrap();
+    assert_eq!(eval(&mut interpreter, "(my-point? p)"), "#t");
+    assert_eq!(eval(&mut interpreter, "(my-point-y p)"), "4");
+    interpreter.eval_str("(set-my-point-x! p 10)").unwrap();
+    let p = interpreter.get("p").unwrap();
+    assert_eq!(MyPoint::from_lisp(&p).unwrap(), MyPoint { x: 10, y: 4 });
+
+    interpreter
+        .define(
+            "server",
+            HTTPServer {
+                host_name: String::from("example"),
+                r#type: 's',
+                port: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        eval(&mut interpreter, "(http-server-host-name server)"),
+        "\"example\""
+    );
+    assert_eq!(eval(&mut interpreter, "(http-server? p)"), "#f");
+}
+
+#[test]
+fn lisp_fn_registers_under_its_name() {
+    let mut interpreter = Interpreter::new();
+    interpreter.register_record::<MyPoint>();
+    interpreter.register_record::<HTTPServer>();
+    register_add_points(&mut interpreter);
+    register_port_or_error(&mut interpreter);
+
+    assert_eq!(
+        eval(
+            &mut interpreter,
+            "(my-point-x (add-points (make-my-point 1 2) (make-my-point 3 4)))"
+        ),
+        "4"
+    );
+    assert!(interpreter.eval_str("(add-points 1 2)").is_err());
+    assert!(interpreter
+        .eval_str("(add-points (make-my-point 1 2))")
+        .is_err());
+
+    assert_eq!(
+        eval(
+            &mut interpreter,
+            "(checked-port (make-http-server \"a\" #\\a 80))"
+        ),
+        "80"
+    );
+    assert_eq!(
+        interpreter.eval_str("(checked-port (make-http-server \"a\" #\\a #f))"),
+        Err(String::from("No port"))
+    );
+    assert!(interpreter.get("port-or-error").is_none());
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use common::{eval, eval_err};
+use lisp::Interpreter;
+
+#[test]
+fn apply_spreads_the_last_argument() {
+    let mut interpreter = Interpreter::new();
+    interpreter
+        .eval_str("(define (sum3 a b c) (+ a b c))")
+        .unwrap();
+    assert_eq!(eval(&mut interpreter, "(apply + 1 2 '(3 4))"), "10");
+    assert_eq!(eval(&mut interpreter, "(apply car '((1 2)))"), "1");
+    assert_eq!(eval(&mut interpreter, "(apply sum3 '(1 2 3))"), "6");
+    assert_eq!(
+        eval_err(&mut interpreter, "(apply + 1 2)"),
+        "Expected list argument to apply, got 2"
+    );
+    eval_err(&mut interpreter, "(apply sum3 '(1 2))");
+}
+
+#[test]
+fn eval_in_the_interaction_environment() {
+    let mut interpreter = Interpreter::new();
+    interpreter.eval_str("(define x 10)").unwrap();
+    assert_eq!(
+        eval(
+            &mut interpreter,
+            "(eval '(+ x 2) (interaction-environment))"
+        ),
+        "12"
+    );
+    interpreter
+        .eval_str("(eval '(define y 5) (interaction-environment))")
+        .unwrap();
+    assert_eq!(eval(&mut interpreter, "y"), "5");
+    assert_eq!(
+        eval_err(&mut interpreter, "(eval 'x 5)"),
+        "Expected environment argument to eval, got 5"
+    );
+}
+
+#[test]
+fn report_environments_are_sandboxed() {
+    let mut interpreter = Interpreter::new();
+    interpreter.eval_str("(define x 10)").unwrap();
+    interpreter
+        .eval_str("(define s (scheme-report-environment 5))")
+        .unwrap();
+    assert_eq!(eval(&mut interpreter, "(environment? s)"), "#t");
+    assert_eq!(eval(&mut interpreter, "(environment? 1)"), "#f");
+
+    interpreter.eval_str("(eval '(define x 99) s)").unwrap();
+    assert_eq!(eval(&mut interpreter, "(eval '(car '(x)) s)"), "x");
+    assert_eq!(eval(&mut interpreter, "(eval 'x s)"), "99");
+    assert_eq!(eval(&mut interpreter, "x"), "10");
+    eval_err(&mut interpreter, "(scheme-report-environment 4)");
+}
+
+#[test]
+fn null_environments_only_have_syntax() {
+    let mut interpreter = Interpreter::new();
+    assert_eq!(
+        eval(&mut interpreter, "(eval '(if #t 1 2) (null-environment 5))"),
+        "1"
+    );
+    assert_eq!(
+        eval_err(&mut interpreter, "(eval '(car '(1)) (null-environment 5))"),
+        "Unbound variable: car"
+    );
+}
@@ -0,0 +1,44 @@
+mod common;
+
+use common::{eval, read};
+use lisp::Interpreter;
+
+#[test]
+fn eq_compares_identity() {
+    let mut interpreter = Interpreter::new();
+    interpreter.eval_str("(define l '(1 2))").unwrap();
+    assert_eq!(eval(&mut interpreter, "(eq? l l)"), "#t");
+    assert_eq!(eval(&mut interpreter, "(eq? '(1) '(1))"), "#f");
+    assert_eq!(eval(&mut interpreter, "(eq? \"a\" \"a\")"), "#f");
+    assert_eq!(eval(&mut interpreter, "(eq? 'a 'a)"), "#t");
+    assert_eq!(eval(&mut interpreter, "(eq? car car)"), "#t");
+}
+
+#[test]
+fn eqv_compares_numbers_and_characters() {
+    let mut interpreter = Interpreter::new();
+    assert_eq!(eval(&mut interpreter, "(eqv? 1.5 1.5)"), "#t");
+    assert_eq!(eval(&mut interpreter, "(eqv? 1 1.0)"), "#f");
+    assert_eq!(eval(&mut interpreter, "(eqv? #\\a #\\a)"), "#t");
+    assert_eq!(eval(&mut interpreter, "(eqv? #t #t)"), "#t");
+    assert_eq!(eval(&mut interpreter, "(eqv? (vector) (vector))"), "#f");
+}
+
+#[test]
+fn equal_compares_structure() {
+    let mut interpreter = Interpreter::new();
+    assert_eq!(
+        eval(&mut interpreter, "(equal? '(1 #(2 \"x\")) '(1 #(2 \"x\")))"),
+        "#t"
+    );
+    assert_eq!(eval(&mut interpreter, "(equal? '(1 2) '(1 2 3))"), "#f");
+    assert_eq!(eval(&mut interpreter, "(equal? \"ab\" \"ab\")"), "#t");
+    assert_eq!(eval(&mut interpreter, "(equal? 2 2.0)"), "#f");
+}
+
+#[test]
+fn partial_eq_matches_equal() {
+    assert_eq!(read("(1 #(2 \"x\") #\\c)"), read("(1 #(2 \"x\") #\\c)"));
+    assert_ne!(read("(1 2)"), read("(1 2.0)"));
+    assert_ne!(read("\"a\""), read("a"));
+}
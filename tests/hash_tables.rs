@@ -0,0 +1,139 @@
+mod common;
+
+use common::{eval, eval_err};
+use lisp::Interpreter;
+
+#[test]
+fn hash_table_procedures() {
+    let mut interpreter = Interpreter::new();
+    interpreter
+        .eval_str("(define h (make-hash-table))")
+        .unwrap();
+    assert_eq!(eval(&mut interpreter, "(hash-table? h)"), "#t");
+    assert_eq!(eval(&mut interpreter, "(hash-table? #(1))"), "#f");
+
+    interpreter.eval_str("(hash-table-set! h 'a 1)").unwrap();
+    interpreter.eval_str("(hash-table-set! h 'b 2)").unwrap();
+    interpreter.eval_str("(hash-table-set! h 'a 3)").unwrap();
+    assert_eq!(eval(&mut interpreter, "(hash-table-ref h 'a)"), "3");
+    assert_eq!(
+        eval(&mut interpreter, "(hash-table-ref/default h 'c 0)"),
+        "0"
+    );
+    assert_eq!(eval(&mut interpreter, "(hash-table-contains? h 'b)"), "#t");
+    assert_eq!(eval(&mut interpreter, "(hash-table-count h)"), "2");
+    assert_eq!(eval(&mut interpreter, "(hash-table-keys h)"), "(a b)");
+    assert_eq!(eval(&mut interpreter, "(hash-table-values h)"), "(3 2)");
+
+    interpreter.eval_str("(hash-table-delete! h 'a)").unwrap();
+    assert_eq!(eval(&mut interpreter, "(hash-table-keys h)"), "(b)");
+    assert_eq!(
+        eval_err(&mut interpreter, "(hash-table-ref h 'a)"),
+        "Key not found in hash table: a"
+    );
+
+    interpreter.eval_str("(hash-table-clear! h)").unwrap();
+    assert_eq!(eval(&mut interpreter, "(hash-table-count h)"), "0");
+}
+
+#[test]
+fn hash_table_update() {
+    let mut interpreter = Interpreter::new();
+    interpreter
+        .eval_str("(define h (make-hash-table))")
+        .unwrap();
+    interpreter.eval_str("(define (inc x) (+ x 1))").unwrap();
+    interpreter.eval_str("(define (zero) 0)").unwrap();
+    interpreter
+        .eval_str("(hash-table-update! h 'n inc zero)")
+        .unwrap();
+    interpreter
+        .eval_str("(hash-table-update!/default h 'n inc 10)")
+        .unwrap();
+    assert_eq!(eval(&mut interpreter, "(hash-table-ref h 'n)"), "2");
+    eval_err(&mut interpreter, "(hash-table-update! h 'm inc)");
+}
+
+#[test]
+fn equivalences() {
+    let mut interpreter = Interpreter::new();
+    interpreter
+        .eval_str("(define equal (make-hash-table equal?))")
+        .unwrap();
+    interpreter
+        .eval_str("(define eqv (make-hash-table eqv?))")
+        .unwrap();
+    interpreter
+        .eval_str("(hash-table-set! equal (list 1 2) 'found)")
+        .unwrap();
+    interpreter
+        .eval_str("(hash-table-set! eqv (list 1 2) 'found)")
+        .unwrap();
+    assert_eq!(
+        eval(
+            &mut interpreter,
+            "(hash-table-ref/default equal (list 1 2) #f)"
+        ),
+        "found"
+    );
+    assert_eq!(
+        eval(
+            &mut interpreter,
+            "(hash-table-ref/default eqv (list 1 2) #f)"
+        ),
+        "#f"
+    );
+
+    interpreter
+        .eval_str("(define strings (make-hash-table string=?))")
+        .unwrap();
+    interpreter
+        .eval_str("(hash-table-set! strings \"k\" 1)")
+        .unwrap();
+    assert_eq!(
+        eval_err(&mut interpreter, "(hash-table-set! strings 'k 1)"),
+        "Expected string key in hash-table-set! for string=? table, got k"
+    );
+    eval_err(&mut interpreter, "(make-hash-table car)");
+}
+
+#[test]
+fn copies_are_independent() {
+    let mut interpreter = Interpreter::new();
+    interpreter
+        .eval_str("(define h (make-hash-table))")
+        .unwrap();
+    interpreter.eval_str("(hash-table-set! h 1 'one)").unwrap();
+    interpreter
+        .eval_str("(define copy (hash-table-copy h))")
+        .unwrap();
+    interpreter
+        .eval_str("(hash-table-set! copy 2 'two)")
+        .unwrap();
+    assert_eq!(eval(&mut interpreter, "(hash-table-count h)"), "1");
+}
+
+#[test]
+fn mutating_with_the_table_itself_as_the_key() {
+    let mut interpreter = Interpreter::new();
+    interpreter
+        .eval_str("(define h (make-hash-table string=?))")
+        .unwrap();
+    assert_eq!(
+        eval_err(&mut interpreter, "(hash-table-set! h h 1)"),
+        "Expected string key in hash-table-set! for string=? table, got #<hash-table 0>"
+    );
+    assert_eq!(
+        eval_err(&mut interpreter, "(hash-table-delete! h h)"),
+        "Expected string key in hash-table-delete! for string=? table, got #<hash-table 0>"
+    );
+
+    //Other tables can hold themselves
+    interpreter
+        .eval_str("(define e (make-hash-table))")
+        .unwrap();
+    interpreter.eval_str("(hash-table-set! e e 1)").unwrap();
+    assert_eq!(eval(&mut interpreter, "(hash-table-ref e e)"), "1");
+    interpreter.eval_str("(hash-table-delete! e e)").unwrap();
+    assert_eq!(eval(&mut interpreter, "(hash-table-count e)"), "0");
+}
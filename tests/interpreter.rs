@@ -0,0 +1,96 @@
+mod common;
+
+use std::fs;
+
+use common::{eval, eval_err, read};
+use lisp::{write, Interpreter};
+
+#[test]
+fn eval_str_returns_the_last_value() {
+    let mut interpreter = Interpreter::new();
+    assert_eq!(
+        eval(&mut interpreter, "(define (sq x) (* x x)) (sq 3) (sq 4)"),
+        "16"
+    );
+    assert_eq!(eval(&mut interpreter, ""), "#<unspecified>");
+    assert_eq!(write(&interpreter.eval(read("(sq 5)")).unwrap()), "25");
+    eval_err(&mut interpreter, "(sq 1");
+}
+
+#[test]
+fn globals_can_be_read_and_defined() {
+    let mut interpreter = Interpreter::new();
+    interpreter.define("limit", 10).unwrap();
+    interpreter.define("names", vec!["a", "b"]).unwrap();
+    assert_eq!(eval(&mut interpreter, "(+ limit 1)"), "11");
+    assert_eq!(eval(&mut interpreter, "names"), "(\"a\" \"b\")");
+    assert!(interpreter.define("wide", u64::MAX).is_err());
+
+    interpreter.eval_str("(define x '(1 2))").unwrap();
+    assert_eq!(write(&interpreter.get("x").unwrap()), "(1 2)");
+    assert!(interpreter.get("undefined").is_none());
+}
+
+#[test]
+fn load_file_evaluates_each_datum() {
+    let path = std::env::temp_dir().join(format!("flisp-load-{}.scm", std::process::id()));
+    fs::write(&path, "(define a 1)\n(define b (+ a 1))\n(* b 10)\n(car").unwrap();
+
+    let mut interpreter = Interpreter::new();
+    let result = interpreter.load_file(&path);
+    fs::remove_file(&path).unwrap();
+
+    //Definitions before the unreadable datum still take effect
+    assert!(result.unwrap_err().starts_with(&path.display().to_string()));
+    assert_eq!(eval(&mut interpreter, "b"), "2");
+    assert!(interpreter.load_file(&path).is_err());
+}
+
+#[test]
+fn bad_programs_are_errors() {
+    let mut interpreter = Interpreter::new();
+    assert_eq!(
+        eval_err(&mut interpreter, "(define 1 2)"),
+        "Expected identifier or list of identifiers in define, found 1"
+    );
+    assert_eq!(
+        eval_err(&mut interpreter, "(+ 2147483647 1)"),
+        "Integer overflow in +"
+    );
+    assert_eq!(
+        eval_err(&mut interpreter, "(- -2147483648 1)"),
+        "Integer overflow in -"
+    );
+    assert_eq!(
+        eval_err(&mut interpreter, "(* 65536 65536)"),
+        "Integer overflow in *"
+    );
+    assert_eq!(
+        eval_err(&mut interpreter, "(/ 1 0)"),
+        "Division by zero in /"
+    );
+    assert_eq!(
+        eval_err(&mut interpreter, "(/ -2147483648 -1)"),
+        "Integer overflow in /"
+    );
+    assert_eq!(
+        eval_err(&mut interpreter, "(remainder 1 0)"),
+        "Division by zero in remainder"
+    );
+    assert_eq!(
+        eval_err(&mut interpreter, "(remainder -2147483648 -1)"),
+        "Integer overflow in remainder"
+    );
+
+    //The interpreter is still usable afterwards
+    assert_eq!(eval(&mut interpreter, "(+ 1 2)"), "3");
+}
+
+#[test]
+fn reals_divide_by_zero() {
+    let mut interpreter = Interpreter::new();
+    assert_eq!(eval(&mut interpreter, "(/ 1.0 0)"), "+inf.0");
+    assert_eq!(eval(&mut interpreter, "(/ -1 0.0)"), "-inf.0");
+    assert_eq!(eval(&mut interpreter, "(+ 2147483647 1.0)"), "2147483600.0");
+    assert_eq!(eval(&mut interpreter, "(remainder 7 2.5)"), "2.0");
+}
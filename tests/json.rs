@@ -0,0 +1,40 @@
+mod common;
+
+use common::{eval, read};
+use lisp::{write, Interpreter};
+
+#[test]
+fn json() {
+    let mut interpreter = Interpreter::new();
+    for json in &[
+        r#"{"a":1,"b":[true,false,null],"c":"text","d":{"e":-2.5}}"#,
+        r#"[1,2.5,-3,"\"quoted\""]"#,
+        r#"[]"#,
+        r#"{}"#,
+        r#"[0.1,1e+20,-2.75]"#,
+    ] {
+        let source = format!(
+            "(scheme->json (json->scheme {}))",
+            write(&read(&format!("{:?}", json)))
+        );
+        assert_eq!(eval(&mut interpreter, &source), format!("{:?}", json));
+    }
+}
+
+#[test]
+fn json_rejects_what_it_cannot_represent() {
+    let mut interpreter = Interpreter::new();
+    assert!(interpreter
+        .eval_str("(json->scheme \"[4294967296]\")")
+        .is_err());
+    assert_eq!(
+        interpreter.eval_str("(json->scheme \"[0.1000000001]\")"),
+        Err(String::from(
+            "JSON real 0.1000000001 needs more than single precision"
+        ))
+    );
+    assert!(interpreter.eval_str("(json->scheme \"[1e300]\")").is_err());
+    interpreter.eval_str("(define v (vector 1))").unwrap();
+    interpreter.eval_str("(vector-set! v 0 v)").unwrap();
+    assert!(interpreter.eval_str("(scheme->json v)").is_err());
+}
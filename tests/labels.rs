@@ -0,0 +1,29 @@
+mod common;
+
+use common::{eval, read, round_trip};
+use lisp::{write, Interpreter};
+
+#[test]
+fn cycle_labels() {
+    assert_eq!(round_trip("#0=#(a #0#)"), "#0=#(a #0#)");
+    assert_eq!(
+        round_trip("#0=#(1 #1=#(2 #1# #0#))"),
+        "#0=#(1 #1=#(2 #1# #0#))"
+    );
+    assert_eq!(round_trip("(x #0=#(#0#) y)"), "(x #0=#(#0#) y)");
+}
+
+#[test]
+fn shared_data_without_cycles_is_written_in_full() {
+    assert_eq!(round_trip("(#0=(a b) #0#)"), "((a b) (a b))");
+}
+
+#[test]
+fn cycles_built_at_runtime() {
+    let mut interpreter = Interpreter::new();
+    interpreter.eval_str("(define v (vector 1 2))").unwrap();
+    interpreter.eval_str("(vector-set! v 1 v)").unwrap();
+    let written = eval(&mut interpreter, "v");
+    assert_eq!(written, "#0=#(1 #0#)");
+    assert_eq!(write(&read(&written)), written);
+}
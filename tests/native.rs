@@ -0,0 +1,43 @@
+mod common;
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use common::{eval, eval_err};
+use lisp::{Arity, Expression, Interpreter};
+
+#[test]
+fn registered_closures_are_procedures() {
+    let mut interpreter = Interpreter::new();
+    let calls = Rc::new(Cell::new(0));
+    let counter = Rc::clone(&calls);
+    interpreter.register_fn("count-args", Arity::AtLeast(0), move |args| {
+        counter.set(counter.get() + 1);
+        Ok(Rc::new(Expression::from(args.len() as i32)))
+    });
+
+    assert_eq!(eval(&mut interpreter, "(count-args 1 (+ 1 2) 'a)"), "3");
+    assert_eq!(
+        eval(&mut interpreter, "count-args"),
+        "#<procedure count-args>"
+    );
+    interpreter.eval_str("(define (call f) (f))").unwrap();
+    assert_eq!(eval(&mut interpreter, "(call count-args)"), "0");
+    assert_eq!(calls.get(), 2);
+}
+
+#[test]
+fn arity_is_checked_before_calling() {
+    let mut interpreter = Interpreter::new();
+    interpreter.register_fn("pair", Arity::Exactly(2), |args| {
+        Ok(Rc::new(Expression::SExpr(args.to_vec())))
+    });
+    interpreter.register_fn("fail", Arity::Between(0, 1), |_| {
+        Err(String::from("Failed on purpose"))
+    });
+
+    assert_eq!(eval(&mut interpreter, "(pair 1 2)"), "(1 2)");
+    eval_err(&mut interpreter, "(pair 1)");
+    eval_err(&mut interpreter, "(fail 1 2)");
+    assert_eq!(eval_err(&mut interpreter, "(fail)"), "Failed on purpose");
+}
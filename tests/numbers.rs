@@ -0,0 +1,57 @@
+mod common;
+
+use common::{eval, round_trip};
+use lisp::{parse_str, Interpreter, Number};
+
+#[test]
+fn decimal_numbers() {
+    assert_eq!(round_trip("42"), "42");
+    assert_eq!(round_trip("-17"), "-17");
+    assert_eq!(round_trip("#d99"), "99");
+    assert_eq!(round_trip("1.5"), "1.5");
+    assert_eq!(round_trip("-0.25"), "-0.25");
+    assert_eq!(round_trip("1e3"), "1000.0");
+    assert_eq!(round_trip("+inf.0"), "+inf.0");
+    assert_eq!(round_trip("-inf.0"), "-inf.0");
+    assert_eq!(round_trip("+nan.0"), "+nan.0");
+}
+
+#[test]
+fn floats_print_in_shortest_form() {
+    for text in &["0.1", "0.3", "3.4028235e38", "1e-7", "123456.79"] {
+        let written = round_trip(text);
+        let original = Number::parse(text, 10).unwrap().to_string();
+        assert_eq!(Number::parse(&written, 10).unwrap().to_string(), original);
+    }
+}
+
+#[test]
+fn numbers_in_each_radix() {
+    let samples = [0, 1, -1, 7, 10, 255, -4096, i32::MAX, i32::MIN];
+    for (prefix, radix) in &[("#b", 2), ("#o", 8), ("#d", 10), ("#x", 16)] {
+        for &x in samples.iter() {
+            let digits = Number::Integer(x).to_string_radix(*radix).unwrap();
+            let text = format!("{}{}", prefix, digits);
+            assert_eq!(round_trip(&text), x.to_string(), "{}", text);
+            assert!(matches!(Number::parse(&digits, *radix), Ok(Number::Integer(y)) if y == x));
+        }
+    }
+}
+
+#[test]
+fn exactness_prefixes() {
+    assert_eq!(round_trip("#x#i10"), "16.0");
+    assert_eq!(round_trip("#e#x10"), "16");
+    assert_eq!(round_trip("#e2.0"), "2");
+    assert!(parse_str("#e1.5").is_err());
+}
+
+#[test]
+fn number_string_procedures() {
+    let mut interpreter = Interpreter::new();
+    assert_eq!(eval(&mut interpreter, "(number->string 255 16)"), "\"ff\"");
+    assert_eq!(eval(&mut interpreter, "(number->string -5 2)"), "\"-101\"");
+    assert_eq!(eval(&mut interpreter, "(string->number \"ff\" 16)"), "255");
+    assert_eq!(eval(&mut interpreter, "(string->number \"#b101\")"), "5");
+    assert_eq!(eval(&mut interpreter, "(string->number \"nope\")"), "#f");
+}
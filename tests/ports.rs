@@ -0,0 +1,141 @@
+mod common;
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+use common::{eval, eval_err};
+use lisp::Interpreter;
+
+//A path in the temporary directory that's unique to this test run
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("flisp-{}-{}", std::process::id(), name))
+}
+
+#[test]
+fn reading_from_string_ports() {
+    let mut interpreter = Interpreter::new();
+    interpreter
+        .eval_str("(define p (open-input-string \"(a b) 42 \\\"s\\\"\\nline two\\n\"))")
+        .unwrap();
+    assert_eq!(eval(&mut interpreter, "(read p)"), "(a b)");
+    assert_eq!(eval(&mut interpreter, "(read p)"), "42");
+    assert_eq!(eval(&mut interpreter, "(read-char p)"), "#\\space");
+    assert_eq!(eval(&mut interpreter, "(peek-char p)"), "#\\\"");
+    assert_eq!(eval(&mut interpreter, "(read p)"), "\"s\"");
+    assert_eq!(eval(&mut interpreter, "(read-line p)"), "\"\"");
+    assert_eq!(eval(&mut interpreter, "(read-line p)"), "\"line two\"");
+    assert_eq!(eval(&mut interpreter, "(eof-object? (read-line p))"), "#t");
+    assert_eq!(eval(&mut interpreter, "(eof-object? (read p))"), "#t");
+    assert_eq!(eval(&mut interpreter, "(char-ready? p)"), "#t");
+}
+
+#[test]
+fn read_reports_incomplete_data() {
+    let mut interpreter = Interpreter::new();
+    assert_eq!(
+        eval_err(&mut interpreter, "(read (open-input-string \"(1\"))"),
+        "Unterminated list, missing `)` at line 1 column 1"
+    );
+}
+
+#[test]
+fn writing_to_string_ports() {
+    let mut interpreter = Interpreter::new();
+    interpreter
+        .eval_str("(define o (open-output-string))")
+        .unwrap();
+    interpreter.eval_str("(write '(x \"y\") o)").unwrap();
+    interpreter.eval_str("(write-string \" z\" o)").unwrap();
+    assert_eq!(
+        eval(&mut interpreter, "(get-output-string o)"),
+        "\"(x \\\"y\\\") z\""
+    );
+    eval_err(
+        &mut interpreter,
+        "(get-output-string (open-input-string \"\"))",
+    );
+}
+
+#[test]
+fn file_ports() {
+    let path = temp_path("ports.txt");
+    let mut interpreter = Interpreter::new();
+    interpreter
+        .define("path", path.display().to_string())
+        .unwrap();
+    interpreter
+        .eval_str("(define (save port) (when #t (write '(1 \"two\") port) (newline port) 'saved))")
+        .unwrap();
+    assert_eq!(
+        eval(&mut interpreter, "(call-with-output-file path save)"),
+        "saved"
+    );
+    assert_eq!(fs::read_to_string(&path).unwrap(), "(1 \"two\")\n");
+    assert_eq!(eval(&mut interpreter, "(file-exists? path)"), "#t");
+
+    interpreter.eval_str("(define (load-it) (read))").unwrap();
+    assert_eq!(
+        eval(&mut interpreter, "(with-input-from-file path load-it)"),
+        "(1 \"two\")"
+    );
+
+    interpreter
+        .eval_str("(define in (open-input-file path))")
+        .unwrap();
+    assert_eq!(eval(&mut interpreter, "(read-char in)"), "#\\(");
+    interpreter.eval_str("(close-port in)").unwrap();
+    eval_err(&mut interpreter, "(read-char in)");
+
+    interpreter.eval_str("(delete-file path)").unwrap();
+    assert_eq!(eval(&mut interpreter, "(file-exists? path)"), "#f");
+    eval_err(&mut interpreter, "(open-input-file path)");
+}
+
+#[test]
+fn current_output_port_can_be_redirected() {
+    let mut interpreter = Interpreter::new();
+    interpreter
+        .eval_str("(define o (open-output-string))")
+        .unwrap();
+    interpreter.eval_str("(current-output-port o)").unwrap();
+    interpreter.eval_str("(display \"shown\")").unwrap();
+    interpreter.eval_str("(write-char #\\!)").unwrap();
+    assert_eq!(
+        eval(&mut interpreter, "(get-output-string o)"),
+        "\"shown!\""
+    );
+    assert_eq!(
+        eval(&mut interpreter, "(eq? (current-output-port) o)"),
+        "#t"
+    );
+    eval_err(
+        &mut interpreter,
+        "(current-output-port (open-input-string \"\"))",
+    );
+}
+
+#[test]
+fn file_output_is_flushed_on_exit() {
+    let script = temp_path("exit.scm");
+    let output = temp_path("exit.txt");
+    fs::write(
+        &script,
+        format!(
+            "(define p (open-output-file {:?}))\n(write 'kept p)\n(exit 3)\n",
+            output.display().to_string()
+        ),
+    )
+    .unwrap();
+
+    let status = Command::new(env!("CARGO_BIN_EXE_lisp"))
+        .arg(&script)
+        .status()
+        .unwrap();
+    let written = fs::read_to_string(&output);
+    fs::remove_file(&script).unwrap();
+    fs::remove_file(&output).unwrap();
+
+    assert_eq!(status.code(), Some(3));
+    assert_eq!(written.unwrap(), "kept");
+}
@@ -0,0 +1,69 @@
+mod common;
+
+use common::{eval, read};
+use lisp::{pretty_print, Interpreter, PrintOptions};
+
+fn pretty(text: &str, width: usize) -> String {
+    let options = PrintOptions {
+        width,
+        ..PrintOptions::default()
+    };
+    pretty_print(&read(text), &options)
+}
+
+#[test]
+fn short_values_stay_on_one_line() {
+    assert_eq!(pretty("(a (b c) \"d\")", 80), "(a (b c) \"d\")");
+}
+
+#[test]
+fn long_values_are_indented() {
+    assert_eq!(
+        pretty("(define (fact n) (if (< n 2) 1 (* n (fact (- n 1)))))", 30),
+        "(define (fact n)\n  (if (< n 2)\n      1\n      (* n (fact (- n 1)))))"
+    );
+    assert_eq!(
+        pretty("(cond ((= x 1) 'one) ((= x 2) 'two) (else 'many))", 20),
+        "(cond ((= x 1) 'one)\n      ((= x 2) 'two)\n      (else 'many))"
+    );
+    assert_eq!(
+        pretty(
+            "(some-function argument-one argument-two argument-three)",
+            30
+        ),
+        "(some-function argument-one\n               argument-two\n               argument-three)"
+    );
+}
+
+#[test]
+fn deep_and_long_values_are_elided() {
+    let options = PrintOptions {
+        max_depth: Some(2),
+        max_length: Some(3),
+        ..PrintOptions::default()
+    };
+    assert_eq!(
+        pretty_print(&read("(1 (2 (3 (4))))"), &options),
+        "(1 (2 ...))"
+    );
+    assert_eq!(
+        pretty_print(&read("(1 2 3 4 5 6)"), &options),
+        "(1 2 3 ...)"
+    );
+    assert_eq!(pretty_print(&read("#(1 2 3 4)"), &options), "#(1 2 3 ...)");
+}
+
+#[test]
+fn pretty_print_procedure() {
+    let mut interpreter = Interpreter::new();
+    interpreter
+        .eval_str("(define p (open-output-string))")
+        .unwrap();
+    interpreter
+        .eval_str("(pretty-print '(a b c d e f) 8 #f 4 p)")
+        .unwrap();
+    assert_eq!(
+        eval(&mut interpreter, "(get-output-string p)"),
+        "\"(a b\\n   c\\n   d\\n   ...)\\n\""
+    );
+}
@@ -0,0 +1,35 @@
+mod common;
+
+use common::{eval, read, round_trip};
+use lisp::{display, Interpreter};
+
+#[test]
+fn write_output_reads_back() {
+    assert_eq!(
+        round_trip("(1 (2 \"three\") #\\a)"),
+        "(1 (2 \"three\") #\\a)"
+    );
+    assert_eq!(round_trip("\"a\\nb\\\"c\\\\\""), "\"a\\nb\\\"c\\\\\"");
+    assert_eq!(round_trip("(1 . 2)"), "(1 . 2)");
+    assert_eq!(round_trip("(quote a)"), "'a");
+    assert_eq!(round_trip("#\\space"), "#\\space");
+    assert_eq!(round_trip("(#t #f ())"), "(#t #f ())");
+}
+
+#[test]
+fn display_prints_text_as_is() {
+    assert_eq!(display(&read("\"a\\tb\"")), "a\tb");
+    assert_eq!(display(&read("#\\x")), "x");
+    assert_eq!(display(&read("(\"a\" #\\b c)")), "(a b c)");
+}
+
+#[test]
+fn procedures_and_unspecified_values() {
+    let mut interpreter = Interpreter::new();
+    assert_eq!(
+        eval(&mut interpreter, "(define (f a b) a)"),
+        "#<procedure f (a b)>"
+    );
+    assert_eq!(eval(&mut interpreter, "car"), "#<procedure car>");
+    assert_eq!(eval(&mut interpreter, "(when #f 1)"), "#<unspecified>");
+}
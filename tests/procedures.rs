@@ -0,0 +1,53 @@
+mod common;
+
+use common::{eval, eval_err};
+use lisp::Interpreter;
+
+#[test]
+fn primitives_are_values() {
+    let mut interpreter = Interpreter::new();
+    interpreter.eval_str("(define plus +)").unwrap();
+    assert_eq!(eval(&mut interpreter, "(plus 1 2)"), "3");
+    interpreter
+        .eval_str("(define (twice f x) (f x x))")
+        .unwrap();
+    assert_eq!(eval(&mut interpreter, "(twice * 5)"), "25");
+    assert_eq!(eval(&mut interpreter, "(apply - '(10 1 2))"), "7");
+}
+
+#[test]
+fn primitives_can_be_shadowed() {
+    let mut interpreter = Interpreter::new();
+    interpreter.eval_str("(define (+ a b) (* a b))").unwrap();
+    assert_eq!(eval(&mut interpreter, "(+ 3 4)"), "12");
+    interpreter
+        .eval_str("(define (f list) (car list))")
+        .unwrap();
+    assert_eq!(eval(&mut interpreter, "(f '(1 2))"), "1");
+    assert_eq!(eval(&mut interpreter, "(list 1 2)"), "(1 2)");
+}
+
+#[test]
+fn arithmetic_errors_name_the_procedure() {
+    let mut interpreter = Interpreter::new();
+    assert_eq!(
+        eval_err(&mut interpreter, "(+ 1 'a)"),
+        "Expected numeric arguments to +, got a"
+    );
+    assert_eq!(
+        eval_err(&mut interpreter, "(- 1 \"b\")"),
+        "Expected numeric arguments to -, got \"b\""
+    );
+    assert_eq!(
+        eval_err(&mut interpreter, "(* #t 2)"),
+        "Expected numeric arguments to *, got #t"
+    );
+    assert_eq!(
+        eval_err(&mut interpreter, "(/ 1 2 '())"),
+        "Expected numeric arguments to /, got ()"
+    );
+    assert_eq!(
+        eval_err(&mut interpreter, "(remainder 1 'c)"),
+        "Expected numeric arguments to remainder, got c"
+    );
+}
@@ -0,0 +1,136 @@
+mod common;
+
+use std::cell::Cell;
+use std::io::{self, BufReader};
+use std::rc::Rc;
+
+use common::{eval, read, round_trip};
+use lisp::{parse_str, write, Expression, Interpreter, Position, Reader};
+
+fn read_all(text: &str) -> Vec<String> {
+    parse_str(text)
+        .unwrap()
+        .iter()
+        .map(|expr| write(expr))
+        .collect()
+}
+
+#[test]
+fn comments_are_skipped() {
+    assert_eq!(read_all("(a ; comment\n b)"), ["(a b)"]);
+    assert_eq!(read_all("a;b"), ["a"]);
+    assert_eq!(read_all("; only a comment"), Vec::<String>::new());
+    assert_eq!(read_all("#| outer #| inner |# still |# 1"), ["1"]);
+    assert_eq!(read_all("(1 #;(2 3) 4)"), ["(1 4)"]);
+    assert_eq!(read_all("#; #; 1 2 3"), ["3"]);
+}
+
+#[test]
+fn unfinished_comments_are_errors() {
+    assert_eq!(
+        parse_str("#| unterminated"),
+        Err(String::from(
+            "Unterminated block comment at line 1 column 1"
+        ))
+    );
+    assert!(parse_str("(1 #;)").is_err());
+}
+
+#[test]
+fn comments_in_evaluated_source() {
+    let mut interpreter = Interpreter::new();
+    let result = interpreter
+        .eval_str("; add them\n(+ 1 #| two |# 2 #;(3))")
+        .unwrap();
+    assert_eq!(write(&result), "3");
+}
+
+#[test]
+fn empty_lists() {
+    assert_eq!(round_trip("()"), "()");
+    assert_eq!(round_trip("(() (()))"), "(() (()))");
+    assert_eq!(*read("()"), Expression::SExpr(Vec::new()));
+    assert_ne!(read("()"), read("#f"));
+
+    let mut interpreter = Interpreter::new();
+    assert_eq!(eval(&mut interpreter, "'()"), "()");
+    assert_eq!(eval(&mut interpreter, "(quote ())"), "()");
+    assert_eq!(eval(&mut interpreter, "(null? (cdr '(1)))"), "#t");
+    assert_eq!(eval(&mut interpreter, "(null? #f)"), "#f");
+    assert_eq!(eval(&mut interpreter, "(eq? '() #f)"), "#f");
+    assert_eq!(eval(&mut interpreter, "(list)"), "()");
+    assert_eq!(eval(&mut interpreter, "(cons 1 '())"), "(1)");
+}
+
+#[test]
+fn brackets_match_their_own_kind() {
+    assert_eq!(round_trip("[a (b) [c]]"), "(a (b) (c))");
+    assert_eq!(round_trip("[1 . 2]"), "(1 . 2)");
+    assert_eq!(
+        parse_str("(let [(x 1)) x)"),
+        Err(String::from(
+            "Expected `]` to close `[` but found `)` at line 1 column 12"
+        ))
+    );
+    assert_eq!(
+        parse_str("(1 2]"),
+        Err(String::from(
+            "Expected `)` to close `(` but found `]` at line 1 column 5"
+        ))
+    );
+
+    let mut interpreter = Interpreter::new();
+    assert_eq!(
+        eval(&mut interpreter, "(cond [#f 1] [else (+ 1 [* 2 3])])"),
+        "7"
+    );
+}
+
+//Hands out one line per read, and records how many lines have been taken
+struct Lines {
+    lines: Vec<&'static str>,
+    taken: Rc<Cell<usize>>,
+}
+
+impl io::Read for Lines {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.lines.get(self.taken.get()) {
+            Some(line) => {
+                buf[..line.len()].copy_from_slice(line.as_bytes());
+                self.taken.set(self.taken.get() + 1);
+                Ok(line.len())
+            }
+            None => Ok(0),
+        }
+    }
+}
+
+#[test]
+fn reader_reads_only_as_much_as_it_needs() {
+    let taken = Rc::new(Cell::new(0));
+    let lines = Lines {
+        lines: vec!["(define x\n", "  (+ 1\n", "2)) 'next\n", "(never read)\n"],
+        taken: Rc::clone(&taken),
+    };
+    let mut reader = Reader::new(BufReader::new(lines));
+
+    let (datum, position) = reader.read().unwrap().unwrap();
+    assert_eq!(write(&datum), "(define x (+ 1 2))");
+    assert_eq!(position, Position { line: 1, column: 1 });
+    assert_eq!(taken.get(), 3);
+
+    let (datum, position) = reader.read().unwrap().unwrap();
+    assert_eq!(write(&datum), "'next");
+    assert_eq!(position, Position { line: 3, column: 5 });
+    assert_eq!(taken.get(), 3);
+}
+
+#[test]
+fn reader_carries_on_after_errors() {
+    let mut reader = Reader::new("(1 2]\n(3)\n(4".as_bytes());
+    assert!(reader.read().is_err());
+    let (datum, _) = reader.read().unwrap().unwrap();
+    assert_eq!(write(&datum), "(3)");
+    assert!(reader.read().is_err());
+    assert_eq!(reader.read(), Ok(None));
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use common::{eval, eval_err};
+use lisp::Interpreter;
+
+#[test]
+fn define_record_type() {
+    let mut interpreter = Interpreter::new();
+    interpreter
+        .eval_str(
+            "(define-record-type point (make-point x y) point? \
+             (x point-x set-point-x!) (y point-y))",
+        )
+        .unwrap();
+    interpreter.eval_str("(define p (make-point 1 2))").unwrap();
+    assert_eq!(eval(&mut interpreter, "p"), "#<point x: 1 y: 2>");
+    assert_eq!(eval(&mut interpreter, "(point? p)"), "#t");
+    assert_eq!(eval(&mut interpreter, "(point? '(1 2))"), "#f");
+    assert_eq!(eval(&mut interpreter, "(point-y p)"), "2");
+
+    interpreter.eval_str("(set-point-x! p 10)").unwrap();
+    assert_eq!(eval(&mut interpreter, "(point-x p)"), "10");
+}
+
+#[test]
+fn record_procedures_check_their_arguments() {
+    let mut interpreter = Interpreter::new();
+    interpreter
+        .eval_str("(define-record-type point (make-point x y) point? (x point-x) (y point-y))")
+        .unwrap();
+    assert_eq!(
+        eval_err(&mut interpreter, "(point-y 5)"),
+        "Expected point record argument to point-y, got 5"
+    );
+    eval_err(&mut interpreter, "(make-point 1)");
+}
+
+#[test]
+fn fields_left_out_of_the_constructor_are_unspecified() {
+    let mut interpreter = Interpreter::new();
+    interpreter
+        .eval_str(
+            "(define-record-type <node> (make-node val) node? \
+             (val node-val) (next node-next set-node-next!))",
+        )
+        .unwrap();
+    assert_eq!(
+        eval(&mut interpreter, "(make-node 1)"),
+        "#<node val: 1 next: #<unspecified>>"
+    );
+}
@@ -0,0 +1,94 @@
+use std::collections::BTreeMap;
+
+use lisp::{from_str, to_string, Interpreter};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Shape {
+    Point,
+    Circle(f32),
+    Rect { width: i32, height: i32 },
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Scene {
+    name: String,
+    initial: char,
+    visible: bool,
+    scale: f64,
+    shapes: Vec<Shape>,
+    tags: BTreeMap<String, i32>,
+    parent: Option<bool>,
+    child: Option<bool>,
+}
+
+#[test]
+fn serde() {
+    let mut tags = BTreeMap::new();
+    tags.insert(String::from("layer"), 3);
+    let scene = Scene {
+        name: String::from("a \"scene\""),
+        initial: 'λ',
+        visible: true,
+        scale: 0.5,
+        shapes: vec![
+            Shape::Point,
+            Shape::Circle(1.25),
+            Shape::Rect {
+                width: 3,
+                height: -4,
+            },
+        ],
+        tags,
+        parent: Some(false),
+        child: None,
+    };
+
+    let text = to_string(&scene).unwrap();
+    assert_eq!(from_str::<Scene>(&text).unwrap(), scene);
+}
+
+#[test]
+fn serde_options_are_unambiguous() {
+    for value in &[None, Some(None), Some(Some(false)), Some(Some(true))] {
+        let text = to_string(value).unwrap();
+        assert_eq!(
+            &from_str::<Option<Option<bool>>>(&text).unwrap(),
+            value,
+            "{}",
+            text
+        );
+    }
+}
+
+#[test]
+fn serde_rejects_lossy_doubles() {
+    assert!(to_string(&std::f64::consts::PI).is_err());
+    assert!(from_str::<f64>("0.1").is_ok());
+    assert_eq!(from_str::<f64>(&to_string(&0.5f64).unwrap()).unwrap(), 0.5);
+}
+
+#[test]
+fn serde_rejects_values_that_contain_themselves() {
+    let mut interpreter = Interpreter::new();
+    interpreter.eval_str("(define v (vector 1 2))").unwrap();
+    interpreter.eval_str("(vector-set! v 1 v)").unwrap();
+    interpreter
+        .eval_str("(define h (make-hash-table))")
+        .unwrap();
+    interpreter.eval_str("(hash-table-set! h 'self h)").unwrap();
+
+    for name in &["v", "h"] {
+        let value = interpreter.get(name).unwrap();
+        assert_eq!(
+            to_string(&value).unwrap_err().to_string(),
+            "Cannot serialize a value that contains itself"
+        );
+        assert!(serde_json::to_string(&value).is_err());
+    }
+
+    //Values that are shared without containing themselves are written in full
+    interpreter.eval_str("(define w (vector 1))").unwrap();
+    let shared = interpreter.eval_str("(list w w)").unwrap();
+    assert_eq!(to_string(&shared).unwrap(), "((1) (1))");
+}
@@ -0,0 +1,116 @@
+mod common;
+
+use common::{eval, eval_err, round_trip};
+use lisp::Interpreter;
+
+#[test]
+fn vector_literals() {
+    assert_eq!(round_trip("#(1 \"two\" #\\3 (4))"), "#(1 \"two\" #\\3 (4))");
+    assert_eq!(round_trip("#u8(0 127 255)"), "#u8(0 127 255)");
+    assert_eq!(round_trip("#()"), "#()");
+    assert!(lisp::parse_str("#u8(256)").is_err());
+}
+
+#[test]
+fn vectors_evaluate_to_themselves() {
+    let mut interpreter = Interpreter::new();
+    assert_eq!(eval(&mut interpreter, "#(1 (+ 1 2))"), "#(1 (+ 1 2))");
+    assert_eq!(eval(&mut interpreter, "#u8(1 2)"), "#u8(1 2)");
+}
+
+#[test]
+fn vector_procedures() {
+    let mut interpreter = Interpreter::new();
+    interpreter.eval_str("(define v (vector 1 2 3 4))").unwrap();
+    assert_eq!(eval(&mut interpreter, "(vector? v)"), "#t");
+    assert_eq!(eval(&mut interpreter, "(vector? '(1))"), "#f");
+    assert_eq!(eval(&mut interpreter, "(vector-length v)"), "4");
+    assert_eq!(eval(&mut interpreter, "(vector-ref v 2)"), "3");
+    assert_eq!(eval(&mut interpreter, "(make-vector 2 'a)"), "#(a a)");
+    assert_eq!(eval(&mut interpreter, "(vector->list v 1 3)"), "(2 3)");
+    assert_eq!(eval(&mut interpreter, "(list->vector '(a b))"), "#(a b)");
+    assert_eq!(eval(&mut interpreter, "(vector-copy v 2)"), "#(3 4)");
+
+    interpreter.eval_str("(vector-set! v 0 'x)").unwrap();
+    interpreter.eval_str("(vector-fill! v 0 2 3)").unwrap();
+    assert_eq!(eval(&mut interpreter, "v"), "#(x 2 0 4)");
+
+    interpreter.eval_str("(define (add a b) (+ a b))").unwrap();
+    assert_eq!(
+        eval(&mut interpreter, "(vector-map add #(1 2 3) #(10 20))"),
+        "#(11 22)"
+    );
+}
+
+#[test]
+fn vector_copies_are_independent() {
+    let mut interpreter = Interpreter::new();
+    interpreter.eval_str("(define v (vector 1 2))").unwrap();
+    interpreter.eval_str("(define w (vector-copy v))").unwrap();
+    interpreter.eval_str("(vector-set! w 0 9)").unwrap();
+    assert_eq!(eval(&mut interpreter, "v"), "#(1 2)");
+}
+
+#[test]
+fn vector_indices_are_checked() {
+    let mut interpreter = Interpreter::new();
+    interpreter.eval_str("(define v (vector 1 2))").unwrap();
+    assert_eq!(
+        eval_err(&mut interpreter, "(vector-ref v 2)"),
+        "Index 2 out of range for vector-ref of length 2"
+    );
+    eval_err(&mut interpreter, "(vector-ref v -1)");
+    eval_err(&mut interpreter, "(vector->list v 2 1)");
+    eval_err(&mut interpreter, "(vector-ref '(1 2) 0)");
+}
+
+#[test]
+fn bytevector_procedures() {
+    let mut interpreter = Interpreter::new();
+    interpreter
+        .eval_str("(define b (bytevector 1 2 3))")
+        .unwrap();
+    assert_eq!(eval(&mut interpreter, "(bytevector-length b)"), "3");
+    assert_eq!(eval(&mut interpreter, "(bytevector-u8-ref b 1)"), "2");
+    assert_eq!(eval(&mut interpreter, "(make-bytevector 2 7)"), "#u8(7 7)");
+    assert_eq!(eval(&mut interpreter, "(bytevector-copy b 1)"), "#u8(2 3)");
+    assert_eq!(
+        eval(&mut interpreter, "(bytevector-append b #u8(4))"),
+        "#u8(1 2 3 4)"
+    );
+    interpreter
+        .eval_str("(bytevector-u8-set! b 0 255)")
+        .unwrap();
+    assert_eq!(eval(&mut interpreter, "b"), "#u8(255 2 3)");
+    eval_err(&mut interpreter, "(bytevector-u8-set! b 0 256)");
+    eval_err(&mut interpreter, "(bytevector 1 -1)");
+}
+
+#[test]
+fn mutating_with_the_vector_itself_as_a_bad_argument() {
+    let mut interpreter = Interpreter::new();
+    interpreter.eval_str("(define v (vector 1 2))").unwrap();
+    interpreter.eval_str("(define b (bytevector 1 2))").unwrap();
+
+    assert_eq!(
+        eval_err(&mut interpreter, "(vector-set! v v 1)"),
+        "Expected index argument to vector-set!, got #(1 2)"
+    );
+    assert_eq!(
+        eval_err(&mut interpreter, "(vector-fill! v 0 v)"),
+        "Expected index argument to vector-fill!, got #(1 2)"
+    );
+    assert_eq!(
+        eval_err(&mut interpreter, "(bytevector-u8-set! b 0 b)"),
+        "Expected byte argument to bytevector-u8-set!, got #u8(1 2)"
+    );
+    assert_eq!(
+        eval_err(&mut interpreter, "(bytevector-u8-set! b b 0)"),
+        "Expected index argument to bytevector-u8-set!, got #u8(1 2)"
+    );
+
+    //A vector can still be stored in itself
+    interpreter.eval_str("(vector-set! v 0 v)").unwrap();
+    interpreter.eval_str("(vector-fill! v v 1)").unwrap();
+    assert_eq!(eval(&mut interpreter, "v"), "#0=#(#0# #0#)");
+}